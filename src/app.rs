@@ -1,32 +1,103 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::mpsc::Sender,
+    time::Duration,
+};
 use redis::{
     self,
-    RedisResult
+    cluster::{ClusterClient, ClusterClientBuilder},
+    ConnectionAddr,
+    ConnectionInfo,
+    ClientTlsConfig,
+    ErrorKind,
+    RedisConnectionInfo,
+    RedisError,
+    RedisResult,
+    TlsCertificates,
+    TlsMode,
+    sentinel::SentinelNodeConnectionInfo,
 };
 use crossterm::event::{KeyEvent, KeyCode};
-use tui::widgets::TableState;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use regex::{Regex, RegexBuilder};
+use tui::{widgets::{ListState, TableState}, style::Color};
+
+use crate::{
+    connection::{Node, SessionConnection, SentinelConnection},
+    decode::{self, Decoder},
+    error::AppError,
+    input::Event,
+    inspect::KeyInfo,
+    json_tree::{self, TreeNode},
+    rules::{Display, DisplayRule},
+    database::{self, RedisServerConf, AddressConf, SentinelAddrConf, AuthConf, TlsConf},
+    server_manager::{ServerManager, ServerManagerAction},
+    stream::{EntryPage, GroupInfo, GroupPending},
+    value::{escape, KeyValue, ListWindow, MemberPage, ValueQuery, ViewedValue, ZRange},
+    worker::{self, RedisReply, RedisRequest, ScanPage, Worker, WorkerEvent},
+};
 
 pub struct App {
     servers: Vec<RedisServer>,
+    /// How the values of keys are shown, by the first rule matching them.
+    display_rules: Vec<DisplayRule>,
+    pub config_path: PathBuf,
     current_tab: usize,
     pub running: bool,
     pub entering_filter: bool,
     pub filter: String,
+    pub server_manager: Option<ServerManager>,
+    pub keyspace_overview: Option<KeyspaceOverview>,
+    pub stream_groups: Option<StreamGroups>,
+    /// Metadata of the key inspected from the key list or the value view.
+    pub key_inspector: Option<KeyInfo>,
+    pub key_columns: KeyColumns,
+    pub prompt: Option<Prompt>,
+    /// Last error, shown in a popup until any key dismisses it.
+    pub error: Option<AppError>,
+    /// Channel the Redis workers reply on.
+    events: Sender<Event<KeyEvent>>,
+    /// Frame of the loading spinner, advanced on every tick.
+    spinner: usize,
 }
 
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 impl App {
-    pub fn new(servers: Vec<RedisServer>, initial_tab: Option<usize>) -> App {
+    pub fn new(
+        servers: Vec<RedisServer>,
+        display_rules: Vec<DisplayRule>,
+        config_path: PathBuf,
+        initial_tab: Option<usize>,
+        events: Sender<Event<KeyEvent>>,
+    ) -> App {
         App {
             servers,
+            display_rules,
+            config_path,
             current_tab: initial_tab.unwrap_or(0),
             running: true,
             entering_filter: false,
             filter: "".to_string(),
+            server_manager: None,
+            keyspace_overview: None,
+            stream_groups: None,
+            key_inspector: None,
+            key_columns: KeyColumns { value_type: true, size: true },
+            prompt: None,
+            error: None,
+            events,
+            spinner: 0,
         }
     }
 
     pub fn set_tab(&mut self, tab: usize) {
-        self.current_tab = tab.clamp(0, self.servers.len() - 1);
+        self.current_tab = tab.min(self.servers.len().saturating_sub(1));
     }
 
     pub fn next_tab(&mut self) {
@@ -34,7 +105,7 @@ impl App {
     }
 
     pub fn prev_tab(&mut self) {
-        self.set_tab(self.current_tab - 1);
+        self.set_tab(self.current_tab.saturating_sub(1));
     }
 
     pub fn current_tab(&self) -> usize {
@@ -45,23 +116,276 @@ impl App {
         &self.servers
     }
 
-    pub fn get_servers_mut(&mut self) -> &mut Vec<RedisServer> {
-        &mut self.servers
+    pub fn get_current_server_mut(&mut self) -> Option<&mut RedisServer> {
+        self.servers.get_mut(self.current_tab)
+    }
+
+    pub fn spinner(&self) -> &'static str {
+        SPINNER[self.spinner % SPINNER.len()]
+    }
+
+    pub fn on_tick(&mut self) {
+        self.spinner = self.spinner.wrapping_add(1);
+        if let Some(session) = self.get_current_session_mut() {
+            session.on_tick();
+        }
+        self.fill_page();
+    }
+
+    /// Requests more keys when the visible key list has empty rows left.
+    fn fill_page(&mut self) {
+        if let Some(session) = self.get_current_session_mut() {
+            session.fill_page();
+        }
+    }
+
+    /// Applies a worker's reply to the server or form that sent the request.
+    pub fn handle_redis_event(&mut self, event: WorkerEvent) {
+        if let RedisReply::Tested(result) = event.reply {
+            let form = self.server_manager.as_mut().and_then(|manager| manager.form.as_mut());
+            if let Some(form) = form.filter(|form| form.testing == Some(event.worker)) {
+                form.testing = None;
+                form.status = Some(result.map(|_| "Connection OK".to_string()));
+            }
+            return;
+        }
+
+        let Some(index) = self.servers.iter().position(|server| server.worker_id() == Some(event.worker)) else {
+            // Reply for a server that was disconnected meanwhile
+            return;
+        };
+        let is_current = index == self.current_tab;
+        let server = &mut self.servers[index];
+
+        if let RedisReply::Connected(nodes) = event.reply {
+            server.connected(nodes);
+            self.fill_page();
+            return;
+        }
+
+        let Some(session) = server.get_session_mut() else { return };
+        let Some(job) = session.finish(event.request) else { return };
+
+        let result = match (job, event.reply) {
+            (Job::Scan(index), RedisReply::Scanned(page)) => session.apply_scan(index, page).map_err(AppError::Scan),
+            (Job::SelectDb, RedisReply::DbSelected(db, result)) => result
+                .map(|_| session.apply_select_db(db))
+                .map_err(|source| AppError::SelectDb { db, source }),
+            (Job::Keyspace, RedisReply::Keyspace(databases)) => match databases {
+                Ok(databases) => {
+                    if is_current {
+                        self.keyspace_overview = Some(KeyspaceOverview::new(databases, session.db));
+                    }
+                    Ok(())
+                }
+                Err(e) => Err(AppError::Keyspace(e)),
+            },
+            (Job::Value, RedisReply::Value(key, value)) => session.apply_value(key, value),
+            (Job::Members, RedisReply::Members(key, page)) => session.apply_members(key, page),
+            (Job::List, RedisReply::List(key, window)) => session.apply_list(key, window),
+            (Job::Entries, RedisReply::Entries(key, page)) => session.apply_entries(key, page),
+            (Job::Groups, RedisReply::Groups(key, groups)) => match groups {
+                Ok(groups) => {
+                    if is_current && session.viewing_key.as_ref() == Some(&key) {
+                        self.stream_groups = Some(StreamGroups::new(key, groups));
+                    }
+                    Ok(())
+                }
+                Err(source) => Err(AppError::ReadKey { key, source }),
+            },
+            (Job::Pending, RedisReply::Pending(key, pending)) => match pending {
+                Ok(pending) => {
+                    if let Some(groups) = self.stream_groups.as_mut().filter(|groups| groups.key == key) {
+                        groups.pending = Some(pending);
+                    }
+                    Ok(())
+                }
+                Err(source) => Err(AppError::ReadKey { key, source }),
+            },
+            (Job::Inspect, RedisReply::Inspected(key, info)) => match info {
+                Ok(Some(info)) => {
+                    session.remember_info(&info);
+                    if is_current {
+                        self.key_inspector = Some(info);
+                    }
+                    Ok(())
+                }
+                Ok(None) => Err(AppError::MissingKey(key)),
+                Err(source) => Err(AppError::ReadKey { key, source }),
+            },
+            _ => Ok(()),
+        };
+        self.report(result);
+        self.fill_page();
+    }
+
+    /// Keeps the error of a failed action for the error popup.
+    pub fn report<T>(&mut self, result: Result<T, AppError>) {
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+
+    fn save_config(&self) -> Result<(), database::DBError> {
+        let db = database::DB_LATEST {
+            version: database::DBVersions::LATEST,
+            server_configs: self.servers.iter().map(RedisServerConf::from).collect(),
+            display_rules: self.display_rules.iter().map(DisplayRule::to_string).collect(),
+        };
+        database::DB::from(db).save(&self.config_path)
+    }
+
+    fn handle_server_manager_input(&mut self, input: KeyEvent) {
+        let Some(manager) = self.server_manager.as_mut() else { return };
+
+        match manager.handle_input(input, &self.servers) {
+            ServerManagerAction::None => return,
+            ServerManagerAction::Test(conf) => {
+                let testing = worker::test_connection(*conf, self.events.clone());
+                if let Some(form) = manager.form.as_mut() {
+                    form.testing = Some(testing);
+                }
+                return;
+            }
+            ServerManagerAction::Close => {
+                self.server_manager = None;
+                return;
+            }
+            ServerManagerAction::Save(Some(index), conf) => {
+                self.servers[index] = RedisServer::from(*conf);
+            }
+            ServerManagerAction::Save(None, conf) => {
+                self.servers.push(RedisServer::from(*conf));
+                manager.selected = self.servers.len() - 1;
+            }
+            ServerManagerAction::Delete(index) => {
+                self.servers.remove(index);
+                manager.selected = manager.selected.min(self.servers.len().saturating_sub(1));
+            }
+        }
+
+        self.set_tab(self.current_tab);
+        let status = self.save_config()
+            .err()
+            .map(|e| AppError::from(e).to_string());
+        if let Some(manager) = self.server_manager.as_mut() {
+            manager.status = status;
+        }
     }
 
-    pub fn push_server(&mut self, server: RedisServer) {
-        self.servers.push(server);
+    fn handle_keyspace_overview_input(&mut self, input: KeyEvent) {
+        let Some(overview) = self.keyspace_overview.as_mut() else { return };
+
+        match input.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => {
+                self.keyspace_overview = None;
+            }
+            KeyCode::Char('j') | KeyCode::Down => overview.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => overview.select_prev(),
+            KeyCode::Enter => {
+                let db = overview.selected_db();
+                self.keyspace_overview = None;
+                if let (Some(db), Some(session)) = (db, self.get_current_session_mut()) {
+                    let result = session.select_db(db).map_err(|source| AppError::SelectDb { db, source });
+                    self.report(result);
+                }
+            }
+            _ => {}
+        }
     }
 
-    pub fn get_current_server(&self) -> &RedisServer {
-        &self.servers[self.current_tab]
+    fn handle_stream_groups_input(&mut self, input: KeyEvent) {
+        let Some(groups) = self.stream_groups.as_mut() else { return };
+
+        match input.code {
+            // Back from the pending entries to the groups first
+            KeyCode::Esc | KeyCode::Char('q') if groups.pending.is_some() => groups.pending = None,
+            KeyCode::Esc | KeyCode::Char('q') => self.stream_groups = None,
+            KeyCode::Char('j') | KeyCode::Down => groups.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => groups.select_prev(),
+            KeyCode::Enter => {
+                let key = groups.key.clone();
+                let group = groups.selected_group().map(|group| group.name.clone());
+                if let (Some(group), Some(session)) = (group, self.get_current_session_mut()) {
+                    session.request_pending(key, group);
+                }
+            }
+            _ => {}
+        }
     }
 
-    pub fn get_current_server_mut(&mut self) -> &mut RedisServer {
-        &mut self.servers[self.current_tab]
+    fn handle_prompt_input(&mut self, input: KeyEvent) {
+        let Some(prompt) = self.prompt.as_mut() else { return };
+
+        match input.code {
+            KeyCode::Esc => {
+                self.prompt = None;
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) => {
+                prompt.input.push(c);
+            }
+            KeyCode::Enter => {
+                let Some(prompt) = self.prompt.take() else { return };
+                let Some(session) = self.get_current_session_mut() else { return };
+                let result = match prompt.kind {
+                    PromptKind::ListIndex => session.jump_to_index(&prompt.input),
+                    PromptKind::ScoreRange => ZRange::parse_score(&prompt.input)
+                        .map(|range| session.set_zset_range(range))
+                        .ok_or(AppError::InvalidRange(prompt.input)),
+                    PromptKind::LexRange => ZRange::parse_lex(&prompt.input)
+                        .map(|range| session.set_zset_range(range))
+                        .ok_or(AppError::InvalidRange(prompt.input)),
+                    PromptKind::Search => session.search(&prompt.input),
+                    PromptKind::Decoders if prompt.input.trim().is_empty() => {
+                        session.set_decoders(None);
+                        Ok(())
+                    }
+                    PromptKind::Decoders => decode::parse_chain(&prompt.input)
+                        .map(|decoders| session.set_decoders(Some(decoders)))
+                        .ok_or(AppError::InvalidDecoders(prompt.input)),
+                };
+                self.report(result);
+            }
+            _ => {}
+        }
     }
 
     pub fn handle_input(&mut self, input: KeyEvent) {
+        if self.error.is_some() {
+            self.error = None;
+            return;
+        }
+
+        if self.server_manager.is_some() {
+            self.handle_server_manager_input(input);
+            return;
+        }
+
+        if self.keyspace_overview.is_some() {
+            self.handle_keyspace_overview_input(input);
+            return;
+        }
+
+        if self.stream_groups.is_some() {
+            self.handle_stream_groups_input(input);
+            return;
+        }
+
+        if self.key_inspector.is_some() {
+            if let KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') = input.code {
+                self.key_inspector = None;
+            }
+            return;
+        }
+
+        if self.prompt.is_some() {
+            self.handle_prompt_input(input);
+            return;
+        }
+
         if self.entering_filter {
             match input.code {
                 KeyCode::Esc => {
@@ -75,10 +399,12 @@ impl App {
                 }
                 KeyCode::Enter => {
                     let filter = self.filter.clone();
-                    let current_server = self.get_current_server_mut();
-                    if current_server.is_connected() {
-                        let session = current_server.get_session_mut().unwrap();
-                        session.scan(filter).expect("to scan");
+                    if let Some(session) = self.get_current_session_mut() {
+                        if session.viewing_key.is_some() {
+                            session.filter_members(filter);
+                        } else {
+                            session.scan(filter);
+                        }
                     }
                     self.entering_filter = false;
                 }
@@ -89,118 +415,434 @@ impl App {
 
         match input.code {
             KeyCode::Char('q') => {
-                let current_server = self.get_current_server_mut();
-                if current_server.is_connected() {
-                    let session = current_server.get_session_mut().unwrap();
+                if let Some(session) = self.get_current_session_mut() {
                     if session.viewing_key.is_some() {
-                        session.viewing_key = None;
+                        session.close_view();
                         return;
                     }
                 }
                 self.running = false;
             }
-            KeyCode::Char('h') => self.prev_tab(),
-            KeyCode::Char('l') => self.next_tab(),
+            KeyCode::Char(c @ ('h' | 'l')) => {
+                // The value view scrolls sideways instead of switching servers
+                let viewing = self.get_current_session_mut().filter(|session| session.viewing_key.is_some());
+                match viewing {
+                    Some(session) => session.scroll_horizontally(c == 'l'),
+                    None if c == 'h' => self.prev_tab(),
+                    None => self.next_tab(),
+                }
+            }
+            KeyCode::Char('w') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.toggle_wrap();
+                }
+            }
             KeyCode::Char('j') => {
-                let current_server = self.get_current_server_mut();
-                if current_server.is_connected() {
-                    let session = current_server.get_session_mut().unwrap();
+                if let Some(session) = self.get_current_session_mut() {
                     if session.viewing_key.is_some() {
-                        session.viewing_key_scroll += 1;
+                        session.scroll_to(session.viewing_key_scroll + 1);
                     } else {
                         session.select_next();
                     }
                 }
             }
             KeyCode::Char('k') => {
-                let current_server = self.get_current_server_mut();
-                if current_server.is_connected() {
-                    let session = current_server.get_session_mut().unwrap();
+                if let Some(session) = self.get_current_session_mut() {
                     if session.viewing_key.is_some() {
-                        session.viewing_key_scroll = session.viewing_key_scroll.saturating_sub(1);
+                        session.scroll_to(session.viewing_key_scroll.saturating_sub(1));
                     } else {
                         session.select_prev();
                     }
                 }
             }
+            KeyCode::Char('g') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.scroll_to(0);
+                }
+            }
+            KeyCode::Char('G') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.scroll_to_end();
+                }
+            }
+            KeyCode::Char(':') => self.open_prompt(PromptKind::ListIndex),
+            KeyCode::Char('S') => self.open_prompt(PromptKind::ScoreRange),
+            KeyCode::Char('L') => self.open_prompt(PromptKind::LexRange),
+            KeyCode::Char('D') => self.open_prompt(PromptKind::Decoders),
+            KeyCode::Char('/') => self.open_prompt(PromptKind::Search),
+            KeyCode::Char(c @ ('n' | 'N')) => {
+                if let Some(session) = self.get_current_session_mut() {
+                    let result = session.next_match(c == 'n');
+                    self.report(result);
+                }
+            }
+            KeyCode::Char('i') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.request_key_info();
+                }
+            }
+            KeyCode::Char('t') => self.key_columns.value_type = !self.key_columns.value_type,
+            KeyCode::Char('m') => self.key_columns.size = !self.key_columns.size,
+            KeyCode::Char('C') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.request_groups();
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.toggle_hex_dump();
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.toggle_order();
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.refresh_value();
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.cycle_auto_refresh();
+                }
+            }
             KeyCode::Char('c') => {
-                self.get_current_server_mut().connect().expect("can connect to server");
+                let events = self.events.clone();
+                if let Some(server) = self.get_current_server_mut() {
+                    server.connect(events);
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(server) = self.get_current_server_mut() {
+                    server.disconnect();
+                }
+            }
+            KeyCode::Char('s') => {
+                self.server_manager = Some(ServerManager::new(self.current_tab));
+            }
+            KeyCode::Char('b') => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.request_keyspace();
+                }
             }
             KeyCode::Char('f') => {
-                self.entering_filter = true;
-                let current_session_pattern = self
-                    .get_current_server()
-                    .get_session()
-                    .unwrap()
-                    .pattern
-                    .clone();
-                self.filter = current_session_pattern;
+                if let Some(session) = self.get_current_session_mut() {
+                    // The value view filters the members of a hash, set or sorted set instead
+                    let current_session_pattern = match &session.viewing_value {
+                        Some(value) if value.members.is_some() => session.member_pattern.clone(),
+                        _ if session.viewing_key.is_some() => return,
+                        _ => session.pattern.clone(),
+                    };
+                    self.entering_filter = true;
+                    self.filter = current_session_pattern;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if self.shows_tree() => {
+                if let Some(session) = self.get_current_session_mut() {
+                    session.toggle_node();
+                }
+            }
+            KeyCode::Char('y') => {
+                let path = self.get_current_session_mut().and_then(|session| session.selected_path());
+                if let Some(path) = path {
+                    let result = copy_to_clipboard(&path).map_err(AppError::Clipboard);
+                    self.report(result);
+                }
             }
             KeyCode::Enter => {
-                let current_server = self.get_current_server_mut();
-                if current_server.is_connected() {
-                    let session = current_server.get_session_mut().unwrap();
+                let server = self.servers.get_mut(self.current_tab);
+                if let Some(session) = server.and_then(RedisServer::get_session_mut) {
                     let selected = session.table_state.selected();
-                    if let Some(selected) = selected {
-                        let (key, _) = session.iter_keys().nth(selected).unwrap();
-                        session.viewing_key = Some(key.clone());
+                    if let Some((key, _)) = selected.and_then(|selected| session.iter_keys().nth(selected)) {
+                        let rule = DisplayRule::find(&self.display_rules, key).cloned();
+                        session.view_key(key.clone(), rule);
                     }
                 }
             }
             KeyCode::Esc => {
-                let current_server = self.get_current_server_mut();
-                if current_server.is_connected() {
-                    let session = current_server.get_session_mut().unwrap();
-                    session.viewing_key = None;
+                let Some(server) = self.get_current_server_mut() else { return };
+                if server.is_connecting() {
+                    server.disconnect();
+                } else if let Some(session) = server.get_session_mut() {
+                    // Cancel what is loading first, then leave the value view
+                    if !session.cancel() {
+                        session.close_view();
+                    }
                 }
             }
             _ => {}
         }
     }
+
+    /// Whether the value view shows a document tree, whose nodes Enter toggles.
+    fn shows_tree(&mut self) -> bool {
+        self.get_current_session_mut().is_some_and(|session| session.tree_nodes().is_some())
+    }
+
+    /// Asks for a line of input about the viewed value, if it is of the type the prompt is for.
+    fn open_prompt(&mut self, kind: PromptKind) {
+        let Some(value) = self.get_current_session_mut().and_then(|session| session.viewing_value.as_ref()) else {
+            return;
+        };
+
+        let applies = match kind {
+            PromptKind::ListIndex => matches!(value.value, KeyValue::List(_)),
+            PromptKind::ScoreRange | PromptKind::LexRange => matches!(value.value, KeyValue::ZSet(_)),
+            PromptKind::Decoders => matches!(value.value, KeyValue::String(_)),
+            PromptKind::Search => true,
+        };
+        if applies {
+            self.prompt = Some(Prompt { kind, input: String::new() });
+        }
+    }
+
+    fn get_current_session_mut(&mut self) -> Option<&mut RedisSession> {
+        self.get_current_server_mut()
+            .and_then(|server| server.get_session_mut())
+    }
 }
 
 pub struct RedisServer {
     pub name: String,
-    pub host: String,
-    port: u16,
-    username: Option<String>,
+    pub address: AddressConf,
+    pub username: Option<String>,
     password: Option<String>,
+    pub db: i64,
+    pub tls: Option<TlsConf>,
+    color: Option<String>,
+    pub read_only: bool,
+    pub cluster: bool,
+    pub connect_error: Option<String>,
+    /// Worker opening the connection, until it replies.
+    connecting: Option<Worker>,
     session: Option<RedisSession>,
 }
 
-impl RedisServer {
-    pub fn new(name: String, host: String, port: u16, username: Option<String>, password: Option<String>) -> RedisServer {
+impl From<RedisServerConf> for RedisServer {
+    fn from(conf: RedisServerConf) -> RedisServer {
         RedisServer {
-            name,
-            host,
-            port,
-            username,
-            password,
+            name: conf.name,
+            address: conf.address,
+            username: conf.auth.as_ref().and_then(|auth| auth.username.clone()),
+            password: conf.auth.map(|auth| auth.password),
+            db: conf.db,
+            tls: conf.tls,
+            color: conf.color,
+            read_only: conf.read_only,
+            cluster: conf.cluster,
+            connect_error: None,
+            connecting: None,
             session: None,
         }
     }
+}
+
+impl From<&RedisServer> for RedisServerConf {
+    fn from(server: &RedisServer) -> RedisServerConf {
+        RedisServerConf {
+            name: server.name.clone(),
+            address: server.address.clone(),
+            auth: server.password.clone().map(|password| AuthConf {
+                username: server.username.clone(),
+                password,
+            }),
+            db: server.db,
+            tls: server.tls.clone(),
+            color: server.color.clone(),
+            read_only: server.read_only,
+            cluster: server.cluster,
+        }
+    }
+}
 
-    pub fn connect(&mut self) -> Result<(), redis::RedisError> {
-        if self.is_connected() { return Ok(()); }
+impl RedisServer {
+    pub fn has_password(&self) -> bool {
+        self.password.is_some()
+    }
 
-        let client = redis::Client::open(format!("redis://{}:{}", self.host, self.port))?;
-        let con = client.get_connection()?;
-        let mut session = RedisSession {
-            client,
-            con,
-            pattern: "*".to_string(),
-            keys: HashMap::new(),
-            cursor: 0,
-            table_state: TableState::default(),
-            viewing_key: None,
-            viewing_key_scroll: 0,
+    pub fn color(&self) -> Option<Color> {
+        self.color.as_ref().and_then(|color| Color::from_str(color).ok())
+    }
+
+    pub fn address(&self) -> String {
+        match &self.address {
+            AddressConf::Tcp { host, port } => format!("{}:{}", host, port),
+            AddressConf::Unix { path } => format!("unix:{}", path.display()),
+            AddressConf::Sentinel { master_name, sentinels, read_replica } => format!(
+                "{}{}@{}",
+                master_name,
+                if *read_replica { " (replica)" } else { "" },
+                sentinels.iter().map(|s| format!("{}:{}", s.host, s.port)).collect::<Vec<_>>().join(","),
+            ),
+        }
+    }
+
+    fn tcp_addr(&self, host: &str, port: u16) -> ConnectionAddr {
+        match &self.tls {
+            Some(tls) => ConnectionAddr::TcpTls {
+                host: host.to_string(),
+                port,
+                insecure: tls.insecure,
+                tls_params: None,
+            },
+            None => ConnectionAddr::Tcp(host.to_string(), port),
+        }
+    }
+
+    fn redis_connection_info(&self) -> RedisConnectionInfo {
+        RedisConnectionInfo {
+            db: self.db,
+            username: self.username.clone(),
+            password: self.password.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn connection_info(&self) -> Result<ConnectionInfo, redis::RedisError> {
+        let addr = match &self.address {
+            AddressConf::Tcp { host, port } => self.tcp_addr(host, *port),
+            AddressConf::Unix { path } => ConnectionAddr::Unix(path.clone()),
+            AddressConf::Sentinel { .. } => return Err(RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "A sentinel setup has no fixed address",
+            ))),
         };
-        session.scan("*".to_string())?;
-        self.session = Some(session);
-        Ok(())
+
+        Ok(ConnectionInfo { addr, redis: self.redis_connection_info() })
+    }
+
+    fn tls_certificates(&self) -> Result<Option<TlsCertificates>, redis::RedisError> {
+        let tls = match (&self.address, &self.tls) {
+            (AddressConf::Tcp { .. }, Some(tls)) => tls,
+            _ => return Ok(None),
+        };
+
+        let root_cert = tls.ca_cert.as_deref().map(read_pem).transpose()?;
+        let client_tls = match (&tls.client_cert, &tls.client_key) {
+            (Some(cert), Some(key)) => Some(ClientTlsConfig {
+                client_cert: read_pem(cert)?,
+                client_key: read_pem(key)?,
+            }),
+            (None, None) => None,
+            _ => return Err(RedisError::from((
+                ErrorKind::InvalidClientConfig,
+                "A TLS client certificate needs both a cert and a key file",
+            ))),
+        };
+
+        if root_cert.is_none() && client_tls.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(TlsCertificates { client_tls, root_cert }))
+    }
+
+    fn client(&self) -> Result<redis::Client, redis::RedisError> {
+        match self.tls_certificates()? {
+            Some(certificates) => redis::Client::build_with_tls(self.connection_info()?, certificates),
+            None => redis::Client::open(self.connection_info()?),
+        }
+    }
+
+    fn cluster_client(&self) -> Result<ClusterClient, redis::RedisError> {
+        let mut builder = ClusterClientBuilder::new(vec![self.connection_info()?])
+            .connection_timeout(Duration::from_secs(5));
+        if let Some(certificates) = self.tls_certificates()? {
+            builder = builder.certs(certificates);
+        }
+        builder.build()
+    }
+
+    /// Sentinels are reached without credentials, the credentials and db are for the announced node.
+    fn sentinel_connection(
+        &self,
+        master_name: &str,
+        sentinels: &[SentinelAddrConf],
+        read_replica: bool,
+    ) -> Result<SentinelConnection, redis::RedisError> {
+        let sentinels = sentinels
+            .iter()
+            .map(|sentinel| ConnectionInfo {
+                addr: self.tcp_addr(&sentinel.host, sentinel.port),
+                redis: RedisConnectionInfo::default(),
+            })
+            .collect();
+        let node_info = SentinelNodeConnectionInfo {
+            tls_mode: self.tls.as_ref().map(|tls| if tls.insecure { TlsMode::Insecure } else { TlsMode::Secure }),
+            redis_connection_info: Some(self.redis_connection_info()),
+        };
+
+        SentinelConnection::connect(sentinels, master_name.to_string(), node_info, read_replica)
+    }
+
+    pub fn open_connection(&self) -> Result<SessionConnection, redis::RedisError> {
+        if let AddressConf::Sentinel { master_name, sentinels, read_replica } = &self.address {
+            let con = self.sentinel_connection(master_name, sentinels, *read_replica)?;
+            return Ok(SessionConnection::Sentinel(Box::new(con)));
+        }
+        if self.cluster {
+            return Ok(SessionConnection::Cluster(Box::new(self.cluster_client()?.get_connection()?)));
+        }
+
+        let con = self.client()?.get_connection_with_timeout(Duration::from_secs(5))?;
+        Ok(SessionConnection::Single(con))
+    }
+
+    pub fn describe_connect_error(&self, e: &RedisError) -> String {
+        let user = self.username.as_deref().unwrap_or("default");
+
+        if e.kind() == ErrorKind::AuthenticationFailed {
+            return format!("Authentication failed for user '{}', check the username and password", user);
+        }
+
+        match e.code() {
+            Some("NOAUTH") => "Authentication required, this server needs a password".to_string(),
+            Some("NOPERM") => format!("User '{}' lacks the ACL permissions rudis needs: {}", user, e.detail().unwrap_or_default()),
+            _ => format!("Could not connect: {}", e),
+        }
+    }
+
+    /// Host cluster nodes announcing no address of their own are reached on.
+    pub fn seed_host(&self) -> &str {
+        match &self.address {
+            AddressConf::Tcp { host, .. } => host.as_str(),
+            AddressConf::Unix { .. } | AddressConf::Sentinel { .. } => "",
+        }
+    }
+
+    /// Starts connecting in the background, `connected` is called with the worker's reply.
+    pub fn connect(&mut self, events: Sender<Event<KeyEvent>>) {
+        if self.is_connected() || self.is_connecting() { return; }
+
+        self.connect_error = None;
+        self.connecting = Some(Worker::connect(RedisServerConf::from(&*self), events));
+    }
+
+    fn connected(&mut self, nodes: RedisResult<Vec<Option<Node>>>) {
+        let Some(worker) = self.connecting.take() else { return };
+
+        match nodes {
+            // The first page of keys is scanned once the key list knows its height
+            Ok(nodes) => self.session = Some(RedisSession::new(worker, self.db, nodes)),
+            Err(e) => self.connect_error = Some(self.describe_connect_error(&e)),
+        }
+    }
+
+    fn worker_id(&self) -> Option<u64> {
+        self.connecting.as_ref()
+            .or(self.session.as_ref().map(|session| &session.worker))
+            .map(Worker::id)
+    }
+
+    pub fn is_connecting(&self) -> bool {
+        self.connecting.is_some()
     }
 
+    /// Stops connecting or disconnects. A connection still being opened is dropped once it is.
     pub fn disconnect(&mut self) {
+        self.connecting = None;
         self.session = None;
     }
 
@@ -217,11 +859,81 @@ impl RedisServer {
     }
 }
 
+fn read_pem(path: &Path) -> Result<Vec<u8>, RedisError> {
+    fs::read(path).map_err(|e| RedisError::from((
+        ErrorKind::InvalidClientConfig,
+        "Could not read TLS file",
+        format!("{}: {}", path.display(), e),
+    )))
+}
+
+/// Puts `text` on the terminal's clipboard with an OSC 52 sequence, which reaches the local
+/// clipboard over SSH too.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))?;
+    stdout.flush()
+}
+
+pub fn human_delta(seconds: u64) -> String {
+    let minute = 60.0;
+    let hour = minute * 60.0;
+    let day = hour * 24.0;
+    let week = day * 7.0;
+    let month = day * 30.0;
+    let year = day * 365.0;
+    let threshold_multiplier = 0.9;
+
+    let f_ttl = seconds as f64;
+
+    if f_ttl > (year * threshold_multiplier) {
+        format!("{:.1}y", f_ttl / year)
+    } else if f_ttl > (month * threshold_multiplier) {
+        format!("{:.1}M", f_ttl / month)
+    } else if f_ttl > (week * threshold_multiplier) {
+        format!("{:.1}w", f_ttl / week)
+    } else if f_ttl > (day * threshold_multiplier) {
+        format!("{:.1}d", f_ttl / day)
+    } else if f_ttl > (hour * threshold_multiplier) {
+        format!("{:.1}h", f_ttl / hour)
+    } else if f_ttl > (minute * threshold_multiplier) {
+        format!("{:.1}m", f_ttl / minute)
+    } else {
+        format!("{}s", f_ttl)
+    }
+}
+
+/// `12KB` style size of `bytes`.
+pub fn human_size(bytes: u64) -> String {
+    let kb_threshold = 1024;
+    let mb_threshold = 1024 * 1024;
+    let gb_threshold = 1024 * 1024 * 1024;
+
+    if bytes < kb_threshold {
+        format!("{}B", bytes)
+    } else if bytes < mb_threshold {
+        format!("{}KB", bytes / kb_threshold)
+    } else if bytes < gb_threshold {
+        format!("{}MB", bytes / mb_threshold)
+    } else {
+        format!("{}GB", bytes / gb_threshold)
+    }
+}
+
+/// Columns of the key list shown besides the key and its TTL.
+#[derive(Clone, Copy)]
+pub struct KeyColumns {
+    pub value_type: bool,
+    pub size: bool,
+}
+
 #[derive(Debug)]
 pub struct KeyMetadata {
-    _type: Option<String>,
-    ttl: Option<u64>,
-    size: Option<u64>,
+    pub _type: Option<String>,
+    /// Seconds left to live, `None` for a persistent key.
+    pub ttl: Option<u64>,
+    /// MEMORY USAGE of the key in bytes.
+    pub size: Option<u64>,
 }
 
 impl KeyMetadata {
@@ -234,114 +946,525 @@ impl KeyMetadata {
 
     pub fn ttl_as_human_delta(&self) -> String {
         match self.ttl {
-            Some(ttl) => {
-                let minute = 60.0;
-                let hour = minute * 60.0;
-                let day = hour * 24.0;
-                let week = day * 7.0;
-                let month = day * 30.0;
-                let year = day * 365.0;
-                let threshold_multiplier = 0.9;
-
-                let f_ttl = ttl as f64;
-
-                if f_ttl > (year as f64 * threshold_multiplier) {
-                    format!("{}y", f_ttl / year)
-                } else if f_ttl > (month * threshold_multiplier) {
-                    format!("{}M", f_ttl / month)
-                } else if f_ttl > (week * threshold_multiplier) {
-                    format!("{}w", f_ttl / week)
-                } else if f_ttl > (day * threshold_multiplier) {
-                    format!("{}d", f_ttl / day)
-                } else if f_ttl > (hour * threshold_multiplier) {
-                    format!("{}h", f_ttl / hour)
-                } else if f_ttl > (minute * threshold_multiplier) {
-                    format!("{}m", f_ttl / minute)
-                } else {
-                    format!("{}s", f_ttl)
-                }
-            }
+            Some(ttl) => human_delta(ttl),
             None => "N/A".to_string(),
         }
     }
 
     pub fn size_as_human(&self) -> String {
         match self.size {
-            Some(size) => {
-                let kb_threshold = 1024;
-                let mb_threshold = 1024 * 1024;
-                let gb_threshold = 1024 * 1024 * 1024;
-
-                if size < kb_threshold {
-                    format!("{}B", size)
-                } else if size < mb_threshold {
-                    format!("{}KB", size / kb_threshold)
-                } else if size < gb_threshold {
-                    format!("{}MB", size / mb_threshold)
-                } else {
-                    format!("{}GB", size / gb_threshold)
-                }
-            }
+            Some(size) => human_size(size),
             None => "-".to_string(),
         }
     }
 }
 
-pub struct RedisSession {
-    client: redis::Client,
-    con: redis::Connection,
-    pub pattern: String,
-    keys: HashMap<String, KeyMetadata>,
-    cursor: u64,
-    pub table_state: TableState,
-    pub viewing_key: Option<String>,
-    pub viewing_key_scroll: u16,
+#[derive(Debug, PartialEq)]
+pub struct KeyspaceInfo {
+    pub db: i64,
+    pub keys: u64,
+    pub expires: u64,
+    /// Average TTL in milliseconds of the keys that have one.
+    pub avg_ttl: u64,
 }
 
-impl RedisSession {
-    fn get_next(&mut self) -> Result<(), redis::RedisError> {
-        // Do a SCAN command
-        let result: RedisResult<(u64, Vec<String>)> = redis::cmd("SCAN")
-            .cursor_arg(self.cursor)
-            .arg("MATCH")
-            .arg(&self.pattern)
-            .query(&mut self.con);
-
-        match result {
-            Ok((new_cursor, keys)) => {
-                self.cursor = new_cursor;
-                for key in keys {
-                    let ttl: Option<u64> = redis::cmd("TTL")
-                        .arg(&key)
-                        .query(&mut self.con)
-                        .unwrap_or(None);
-                    self.keys.insert(key, KeyMetadata { _type: None, ttl, size: None });
-                }
-                Ok(())
-            }
-            Err(e) => Err(e),
+impl KeyspaceInfo {
+    pub fn avg_ttl_as_human_delta(&self) -> String {
+        if self.avg_ttl == 0 {
+            return "-".to_string();
         }
+        human_delta(self.avg_ttl / 1000)
     }
+}
 
-    pub fn scan(&mut self, pattern: String) -> Result<(), redis::RedisError> {
-        if pattern != self.pattern {
-            self.pattern = pattern;
-            self.keys.clear();
-            self.cursor = 0;
-        }
+/// Parses `INFO keyspace` lines like `db0:keys=1,expires=0,avg_ttl=0`.
+pub fn parse_keyspace_info(info: &str) -> Vec<KeyspaceInfo> {
+    info.lines()
+        .filter_map(|line| {
+            let (db, stats) = line.trim().split_once(':')?;
+            let db = db.strip_prefix("db")?.parse().ok()?;
+            let mut keyspace = KeyspaceInfo { db, keys: 0, expires: 0, avg_ttl: 0 };
 
-        self.get_next()
-    }
+            for stat in stats.split(',') {
+                let (name, value) = stat.split_once('=')?;
+                let value = value.parse().unwrap_or(0);
+                match name {
+                    "keys" => keyspace.keys = value,
+                    "expires" => keyspace.expires = value,
+                    "avg_ttl" => keyspace.avg_ttl = value,
+                    _ => {}
+                }
+            }
 
-    pub fn next(&mut self) -> Result<(), redis::RedisError> {
-        if self.cursor == 0 {
-            return Ok(());
-        }
+            Some(keyspace)
+        })
+        .collect()
+}
 
-        self.get_next()
-    }
+pub struct KeyspaceOverview {
+    pub databases: Vec<KeyspaceInfo>,
+    pub table_state: TableState,
+}
 
-    pub fn iter_keys(&self) -> impl Iterator<Item = (&String, &KeyMetadata)> {
+impl KeyspaceOverview {
+    pub fn new(databases: Vec<KeyspaceInfo>, current_db: i64) -> KeyspaceOverview {
+        let mut table_state = TableState::default();
+        table_state.select(databases.iter().position(|info| info.db == current_db));
+
+        KeyspaceOverview { databases, table_state }
+    }
+
+    pub fn select_next(&mut self) {
+        let i = self.table_state.selected().map_or(0, |i| i + 1);
+        self.table_state.select(Some(i.min(self.databases.len().saturating_sub(1))));
+    }
+
+    pub fn select_prev(&mut self) {
+        let i = self.table_state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.table_state.select(Some(i));
+    }
+
+    pub fn selected_db(&self) -> Option<i64> {
+        self.table_state.selected()
+            .and_then(|i| self.databases.get(i))
+            .map(|info| info.db)
+    }
+}
+
+/// Consumer groups of the viewed stream, with the consumers and pending entries of the group
+/// drilled into.
+pub struct StreamGroups {
+    pub key: Vec<u8>,
+    pub groups: Vec<GroupInfo>,
+    pub table_state: TableState,
+    pub pending: Option<GroupPending>,
+}
+
+impl StreamGroups {
+    pub fn new(key: Vec<u8>, groups: Vec<GroupInfo>) -> StreamGroups {
+        let mut table_state = TableState::default();
+        if !groups.is_empty() {
+            table_state.select(Some(0));
+        }
+
+        StreamGroups { key, groups, table_state, pending: None }
+    }
+
+    pub fn select_next(&mut self) {
+        let i = self.table_state.selected().map_or(0, |i| i + 1);
+        self.table_state.select(Some(i.min(self.groups.len().saturating_sub(1))));
+    }
+
+    pub fn select_prev(&mut self) {
+        let i = self.table_state.selected().map_or(0, |i| i.saturating_sub(1));
+        self.table_state.select(Some(i));
+    }
+
+    pub fn selected_group(&self) -> Option<&GroupInfo> {
+        self.table_state.selected().and_then(|i| self.groups.get(i))
+    }
+}
+
+/// What the line typed in the prompt popup is for.
+#[derive(Clone, Copy)]
+pub enum PromptKind {
+    /// Index of the list element to scroll to, negative ones count from the tail.
+    ListIndex,
+    /// `min max` ZRANGEBYSCORE bounds of the sorted set members to show.
+    ScoreRange,
+    /// `min max` ZRANGEBYLEX bounds of the sorted set members to show.
+    LexRange,
+    /// Decoders to run the viewed string through, like `base64 gzip msgpack`.
+    Decoders,
+    /// Regex to search the value view for.
+    Search,
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::ListIndex => "Go to index",
+            PromptKind::ScoreRange => "Score range, like (1 +inf, empty for all",
+            PromptKind::LexRange => "Lex range, like [a (b, empty for all",
+            PromptKind::Decoders => "Decoders, like base64 gzip msgpack, empty to detect",
+            PromptKind::Search => "Search regex, case-insensitive without capitals, empty to clear",
+        }
+    }
+}
+
+/// A line of input asked for in a popup, applied on Enter.
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+/// Columns h and l scroll the value view sideways by.
+const HSCROLL_STEP: usize = 8;
+
+const AUTO_REFRESH: [Option<Duration>; 4] = [
+    None,
+    Some(Duration::from_secs(1)),
+    Some(Duration::from_secs(5)),
+    Some(Duration::from_secs(30)),
+];
+
+/// SCAN position on one node. A single server has one cursor, a cluster one per master.
+struct ScanCursor {
+    node: Option<Node>,
+    cursor: u64,
+    done: bool,
+}
+
+impl ScanCursor {
+    fn new(node: Option<Node>) -> ScanCursor {
+        ScanCursor { node, cursor: 0, done: false }
+    }
+
+    fn reset(&mut self) {
+        self.cursor = 0;
+        self.done = false;
+    }
+}
+
+/// What a request sent to the worker is waiting for.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Job {
+    /// A page of keys for the cursor at this index.
+    Scan(usize),
+    SelectDb,
+    Keyspace,
+    Value,
+    /// The next page of members of the viewed collection.
+    Members,
+    /// Other elements of the viewed list.
+    List,
+    /// The next page of entries of the viewed stream.
+    Entries,
+    /// Consumer groups of the viewed stream.
+    Groups,
+    /// Consumers and pending entries of a consumer group.
+    Pending,
+    /// Metadata of a key for the inspector.
+    Inspect,
+}
+
+impl Job {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Job::Scan(_) => "scanning",
+            Job::SelectDb => "switching db",
+            Job::Keyspace => "reading keyspace",
+            Job::Value => "loading value",
+            Job::Members => "loading members",
+            Job::List => "loading elements",
+            Job::Entries => "loading entries",
+            Job::Groups => "reading groups",
+            Job::Pending => "reading pending entries",
+            Job::Inspect => "inspecting key",
+        }
+    }
+}
+
+pub struct RedisSession {
+    worker: Worker,
+    cluster: bool,
+    pub db: i64,
+    pub pattern: String,
+    keys: HashMap<Vec<u8>, KeyMetadata>,
+    cursors: Vec<ScanCursor>,
+    /// Requests sent to the worker whose reply has not arrived yet.
+    pending: Vec<(u64, Job)>,
+    /// Rows the key list has room for, scanning continues until they are filled.
+    pub page_height: u16,
+    /// Columns the lines of the value view have, wrapped strings are broken at it.
+    pub view_width: u16,
+    pub table_state: TableState,
+    pub viewing_key: Option<Vec<u8>>,
+    pub viewing_value: Option<ViewedValue>,
+    /// First row of the value view, the index of the first element shown for a list.
+    pub viewing_key_scroll: usize,
+    /// Columns the lines of the value view are scrolled right by, when they are not wrapped.
+    pub viewing_key_hscroll: usize,
+    /// Wrap the long lines of the viewed string, rather than scrolling them sideways.
+    pub wrap: bool,
+    /// MATCH pattern for the members of the viewed collection.
+    pub member_pattern: String,
+    /// Members of the viewed sorted set, when they are not filtered by `member_pattern`.
+    pub zset_range: ZRange,
+    /// Show the viewed string as a hex dump, `None` to only do so for binary ones.
+    pub hex_dump: Option<bool>,
+    /// Decoders to run the viewed string through, `None` to detect them.
+    pub decoders: Option<Vec<Decoder>>,
+    /// Config rule the viewed key matched, until another display is picked for it.
+    pub rule: Option<DisplayRule>,
+    /// JSONPaths of the collapsed nodes of the viewed document.
+    pub collapsed: HashSet<String>,
+    /// Selected row of the document tree, kept with the row it is scrolled to.
+    pub tree_state: ListState,
+    /// Regex searched for in the value view, its matches are highlighted.
+    pub search: Option<Regex>,
+    /// Show the viewed sorted set from the highest score down, the viewed stream from the newest
+    /// entry.
+    pub rev: bool,
+    /// Refetch the viewed value this often, `None` to only refetch on request.
+    pub auto_refresh: Option<Duration>,
+}
+
+impl RedisSession {
+    fn new(worker: Worker, db: i64, nodes: Vec<Option<Node>>) -> RedisSession {
+        RedisSession {
+            worker,
+            cluster: nodes.iter().any(Option::is_some),
+            db,
+            pattern: "*".to_string(),
+            keys: HashMap::new(),
+            cursors: nodes.into_iter().map(ScanCursor::new).collect(),
+            pending: Vec::new(),
+            page_height: 0,
+            view_width: 0,
+            table_state: TableState::default(),
+            viewing_key: None,
+            viewing_value: None,
+            viewing_key_scroll: 0,
+            viewing_key_hscroll: 0,
+            wrap: true,
+            member_pattern: "*".to_string(),
+            zset_range: ZRange::Rank,
+            hex_dump: None,
+            decoders: None,
+            rule: None,
+            collapsed: HashSet::new(),
+            tree_state: ListState::default(),
+            search: None,
+            rev: false,
+            auto_refresh: None,
+        }
+    }
+
+    fn request(&mut self, job: Job, request: RedisRequest) {
+        let id = self.worker.send(request);
+        self.pending.push((id, job));
+    }
+
+    /// Takes the job a reply answers, `None` when it was cancelled or superseded.
+    fn finish(&mut self, request: u64) -> Option<Job> {
+        let index = self.pending.iter().position(|(id, _)| *id == request)?;
+        Some(self.pending.remove(index).1)
+    }
+
+    fn is_pending(&self, matches: impl Fn(&Job) -> bool) -> bool {
+        self.pending.iter().any(|(_, job)| matches(job))
+    }
+
+    /// Label of the oldest request still in flight.
+    pub fn pending_label(&self) -> Option<&'static str> {
+        self.pending.first().map(|(_, job)| job.label())
+    }
+
+    /// Stops waiting for every request in flight, returning whether there were any. The worker
+    /// still finishes them, their replies are dropped.
+    pub fn cancel(&mut self) -> bool {
+        if self.pending.is_empty() {
+            return false;
+        }
+
+        for (_, job) in std::mem::take(&mut self.pending) {
+            match job {
+                // Stop paging so the scan is not sent again right away, a new scan starts over
+                Job::Scan(index) => self.cursors[index].done = true,
+                // Only a first load leaves nothing to show, a refresh keeps the value on screen
+                Job::Value if self.viewing_value.is_none() => self.close_view(),
+                Job::Value => {}
+                Job::Members => {
+                    if let Some(members) = self.viewing_value.as_mut().and_then(|value| value.members.as_mut()) {
+                        members.done = true;
+                    }
+                }
+                Job::List => {
+                    // Back to the elements already read, or the window would be requested again
+                    if let Some(KeyValue::List(window)) = self.viewing_value.as_ref().map(|value| &value.value) {
+                        self.viewing_key_scroll = window.start;
+                    }
+                }
+                Job::Entries => {
+                    if let Some(KeyValue::Stream(stream)) = self.viewing_value.as_mut().map(|value| &mut value.value) {
+                        stream.next = None;
+                    }
+                }
+                Job::Groups | Job::Pending | Job::Inspect => {}
+                Job::SelectDb | Job::Keyspace => {}
+            }
+        }
+        true
+    }
+
+    fn get_next(&mut self) {
+        if self.is_pending(|job| matches!(job, Job::Scan(_))) {
+            return;
+        }
+        let Some(index) = self.cursors.iter().position(|cursor| !cursor.done) else {
+            return;
+        };
+
+        // Do a SCAN command on the first node that still has keys left
+        let cursor = &self.cursors[index];
+        let request = RedisRequest::Scan {
+            node: cursor.node.clone(),
+            cursor: cursor.cursor,
+            pattern: self.pattern.clone(),
+        };
+        self.request(Job::Scan(index), request);
+    }
+
+    fn apply_scan(&mut self, index: usize, page: RedisResult<ScanPage>) -> Result<(), redis::RedisError> {
+        let scan_cursor = &mut self.cursors[index];
+
+        match page {
+            Ok(page) => {
+                scan_cursor.cursor = page.cursor;
+                scan_cursor.done = page.cursor == 0;
+                self.keys.extend(page.keys);
+                Ok(())
+            }
+            Err(e) => {
+                // Stop paging this node so the error is not raised again on every redraw. A new
+                // scan starts over.
+                scan_cursor.done = true;
+                Err(e)
+            }
+        }
+    }
+
+    fn reset_scan(&mut self) {
+        // Pages still in flight belong to the old scan
+        self.pending.retain(|(_, job)| !matches!(job, Job::Scan(_)));
+        self.cursors.iter_mut().for_each(ScanCursor::reset);
+    }
+
+    pub fn is_cluster(&self) -> bool {
+        self.cluster
+    }
+
+    pub fn select_db(&mut self, db: i64) -> Result<(), redis::RedisError> {
+        if self.is_cluster() && db != 0 {
+            return Err(RedisError::from((ErrorKind::ClientError, "Redis Cluster only supports db 0")));
+        }
+
+        self.request(Job::SelectDb, RedisRequest::SelectDb(db));
+        Ok(())
+    }
+
+    fn apply_select_db(&mut self, db: i64) {
+        self.db = db;
+        self.keys.clear();
+        self.reset_scan();
+        self.table_state = TableState::default();
+        self.get_next();
+    }
+
+    pub fn request_keyspace(&mut self) {
+        self.request(Job::Keyspace, RedisRequest::Keyspace);
+    }
+
+    pub fn scan(&mut self, pattern: String) {
+        if pattern != self.pattern {
+            self.pattern = pattern;
+            self.keys.clear();
+            self.reset_scan();
+        } else if self.done() {
+            self.reset_scan();
+        }
+
+        self.get_next()
+    }
+
+    pub fn next(&mut self) {
+        if self.done() {
+            return;
+        }
+
+        self.get_next()
+    }
+
+    /// Scans more keys while the key list has empty rows left, or more members while the value
+    /// view does.
+    pub fn fill_page(&mut self) {
+        if self.viewing_key.is_some() {
+            self.fill_members();
+            self.fill_list();
+            self.fill_entries();
+        } else if self.page_height as usize > self.count() {
+            self.next();
+        }
+    }
+
+    fn fill_members(&mut self) {
+        if self.is_pending(|job| matches!(job, Job::Value | Job::Members)) {
+            return;
+        }
+        let (Some(key), Some(value)) = (&self.viewing_key, &self.viewing_value) else { return };
+        let Some(members) = value.members.as_ref().filter(|members| !members.done) else { return };
+        if value.value.loaded() >= self.viewing_key_scroll + self.page_height as usize {
+            return;
+        }
+
+        let request = RedisRequest::Members {
+            key: key.clone(),
+            collection: members.collection,
+            cursor: members.cursor,
+            query: self.value_query(),
+        };
+        self.request(Job::Members, request);
+    }
+
+    /// Elements of a list to read around the rows on screen, one screen before and after them
+    /// so scrolling does not wait on every row.
+    fn list_window(&self) -> Range<usize> {
+        let rows = (self.page_height as usize).max(1);
+        self.viewing_key_scroll.saturating_sub(rows)..self.viewing_key_scroll + 2 * rows
+    }
+
+    fn fill_entries(&mut self) {
+        if self.is_pending(|job| matches!(job, Job::Value | Job::Entries)) {
+            return;
+        }
+        let (Some(key), Some(value)) = (&self.viewing_key, &self.viewing_value) else { return };
+        let KeyValue::Stream(stream) = &value.value else { return };
+        let Some(from) = stream.next.clone() else { return };
+        if stream.entries.len() >= self.viewing_key_scroll + self.page_height as usize {
+            return;
+        }
+
+        let request = RedisRequest::Entries { key: key.clone(), from, rev: self.rev };
+        self.request(Job::Entries, request);
+    }
+
+    /// The parts of the viewed value to read.
+    fn value_query(&self) -> ValueQuery {
+        ValueQuery {
+            pattern: self.member_pattern.clone(),
+            window: self.list_window(),
+            zrange: self.zset_range.clone(),
+            rev: self.rev,
+            decoders: self.decoders.clone(),
+        }
+    }
+
+    fn fill_list(&mut self) {
+        if self.is_pending(|job| matches!(job, Job::Value | Job::List)) {
+            return;
+        }
+        let (Some(key), Some(value)) = (&self.viewing_key, &self.viewing_value) else { return };
+        let KeyValue::List(window) = &value.value else { return };
+        if window.covers(self.viewing_key_scroll, self.page_height as usize) {
+            return;
+        }
+
+        let request = RedisRequest::List { key: key.clone(), window: self.list_window() };
+        self.request(Job::List, request);
+    }
+
+    pub fn iter_keys(&self) -> impl Iterator<Item = (&Vec<u8>, &KeyMetadata)> {
         self.keys.iter()
     }
 
@@ -349,11 +1472,16 @@ impl RedisSession {
         self.keys.len()
     }
 
+    /// Whether every node has been scanned to the end.
     pub fn done(&self) -> bool {
-        self.cursor == 0
+        self.cursors.iter().all(|cursor| cursor.done)
     }
 
     pub fn select_next(&mut self) {
+        if self.count() == 0 {
+            return;
+        }
+
         let i = match self.table_state.selected() {
             Some(i) => {
                 if i >= self.count() - 1 {
@@ -368,6 +1496,10 @@ impl RedisSession {
     }
 
     pub fn select_prev(&mut self) {
+        if self.count() == 0 {
+            return;
+        }
+
         let i = match self.table_state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -381,11 +1513,433 @@ impl RedisSession {
         self.table_state.select(Some(i));
     }
 
-    pub fn get_viewing_key(&mut self) -> Option<String> {
-        let result: RedisResult<String> = redis::cmd("GET")
-            .arg(self.viewing_key.as_ref().unwrap())
-            .query(&mut self.con);
+    /// Opens `key` in the value view, shown the way `rule` says when it matched one.
+    pub fn view_key(&mut self, key: Vec<u8>, rule: Option<DisplayRule>) {
+        self.viewing_key = Some(key.clone());
+        self.viewing_value = None;
+        self.viewing_key_scroll = 0;
+        self.member_pattern = "*".to_string();
+        self.zset_range = ZRange::Rank;
+        self.hex_dump = None;
+        self.decoders = None;
+        self.rev = false;
+        self.collapsed.clear();
+        self.tree_state = ListState::default();
+        self.search = None;
+        self.viewing_key_hscroll = 0;
+        match rule.as_ref().map(|rule| &rule.display) {
+            Some(Display::Decoders(decoders)) => self.decoders = Some(decoders.clone()),
+            Some(Display::Hex) => self.hex_dump = Some(true),
+            None => {}
+        }
+        self.rule = rule;
+        self.request(Job::Value, RedisRequest::Fetch { key, query: self.value_query() });
+    }
+
+    /// Fetches the viewed value again, keeping the current one on screen until it arrives.
+    pub fn refresh_value(&mut self) {
+        if self.is_pending(|job| *job == Job::Value) {
+            return;
+        }
+        if let Some(key) = self.viewing_key.clone() {
+            // Members are paged in again from the first one
+            self.pending.retain(|(_, job)| !matches!(job, Job::Members | Job::List | Job::Entries));
+            self.request(Job::Value, RedisRequest::Fetch { key, query: self.value_query() });
+        }
+    }
+
+    /// Reloads the viewed collection with only the members matching `pattern`. Sorted sets are
+    /// scanned, out of order, while a pattern is set.
+    pub fn filter_members(&mut self, pattern: String) {
+        if pattern == self.member_pattern {
+            return;
+        }
+
+        self.member_pattern = pattern;
+        self.reload_members();
+    }
+
+    /// Reloads the viewed sorted set with the members in `range`.
+    pub fn set_zset_range(&mut self, range: ZRange) {
+        self.zset_range = range;
+        self.reload_members();
+    }
+
+    /// Whether the viewed string is shown as a hex dump rather than as text.
+    pub fn shows_hex_dump(&self) -> bool {
+        let binary = self.viewing_value.as_ref().is_some_and(|value| value.binary);
+        self.hex_dump.unwrap_or(binary)
+    }
+
+    /// Dumps the viewed string as hex, or drops the dump, as it is now shown.
+    fn update_hex_dump(&mut self) {
+        let shown = self.shows_hex_dump();
+        if let Some(value) = self.viewing_value.as_mut() {
+            value.show_hex_dump(shown);
+        }
+    }
+
+    pub fn toggle_hex_dump(&mut self) {
+        let is_string = self.viewing_value.as_ref().is_some_and(|value| matches!(value.value, KeyValue::String(_)));
+        if is_string {
+            self.hex_dump = Some(!self.shows_hex_dump());
+            self.viewing_key_scroll = 0;
+            self.update_hex_dump();
+        }
+    }
+
+    /// Fetches the viewed string again to run it through `decoders`, or the ones it looks encoded
+    /// with when `None`.
+    pub fn set_decoders(&mut self, decoders: Option<Vec<Decoder>>) {
+        self.decoders = decoders;
+        self.rule = None;
+        self.hex_dump = None;
+        self.reload_members();
+    }
+
+    /// Flips the order sorted sets and streams are read in.
+    pub fn toggle_order(&mut self) {
+        let ordered = self.viewing_value.as_ref()
+            .is_some_and(|value| matches!(value.value, KeyValue::ZSet(_) | KeyValue::Stream(_)));
+        if ordered {
+            self.rev = !self.rev;
+            self.reload_members();
+        }
+    }
+
+    /// Reads the viewed collection again from its first member.
+    fn reload_members(&mut self) {
+        self.viewing_key_scroll = 0;
+        self.pending.retain(|(_, job)| *job != Job::Value);
+        self.refresh_value();
+    }
+
+    /// Scrolls the value view, lists no further than their last element and document trees no
+    /// further than their last node.
+    pub fn scroll_to(&mut self, row: usize) {
+        let tree_len = self.tree_nodes().map(|nodes| nodes.len());
+        self.viewing_key_scroll = match (self.viewing_value.as_ref().map(|value| &value.value), tree_len) {
+            (_, Some(len)) => row.min(len.saturating_sub(1)),
+            (Some(KeyValue::List(window)), _) => row.min(window.len.saturating_sub(1)),
+            _ => row,
+        };
+    }
+
+    /// The rows of the viewed document tree, `None` when the value is not shown as one.
+    pub fn tree_nodes(&self) -> Option<Vec<TreeNode<'_>>> {
+        let document = self.viewing_value.as_ref()?.document.as_ref()?;
+        (!self.shows_hex_dump()).then(|| json_tree::visible_nodes(document, &self.collapsed))
+    }
+
+    /// JSONPath of the selected node of the viewed document tree.
+    pub fn selected_path(&self) -> Option<String> {
+        let mut nodes = self.tree_nodes()?;
+        (self.viewing_key_scroll < nodes.len()).then(|| nodes.swap_remove(self.viewing_key_scroll).path)
+    }
+
+    /// Searches the value view for the regex `pattern`, case-insensitive unless it has capitals,
+    /// and scrolls to its first match from the current row. An empty pattern ends the search.
+    pub fn search(&mut self, pattern: &str) -> Result<(), AppError> {
+        if pattern.is_empty() {
+            self.search = None;
+            return Ok(());
+        }
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(!pattern.chars().any(char::is_uppercase))
+            .build()
+            .map_err(|source| AppError::InvalidSearch { pattern: pattern.to_string(), source })?;
+        self.search = Some(regex);
+        self.scroll_to_match(self.viewing_key_scroll, true)
+    }
+
+    /// Scrolls to the next row matching the search, or the previous one, wrapping around.
+    pub fn next_match(&mut self, forward: bool) -> Result<(), AppError> {
+        let from = if forward { self.viewing_key_scroll + 1 } else { self.viewing_key_scroll };
+        self.scroll_to_match(from, forward)
+    }
+
+    /// Scrolls to the first matching row from `from` on, or the last one before it.
+    fn scroll_to_match(&mut self, from: usize, forward: bool) -> Result<(), AppError> {
+        let Some(search) = self.search.clone() else { return Ok(()) };
+        let matches: Vec<usize> = self.text_rows()
+            .into_iter()
+            .filter(|(_, text)| search.is_match(text))
+            .map(|(row, _)| row)
+            .collect();
+
+        let row = if forward {
+            matches.iter().find(|&&row| row >= from).or(matches.first())
+        } else {
+            matches.iter().rev().find(|&&row| row < from).or(matches.last())
+        };
+        match row {
+            Some(&row) => {
+                self.scroll_to(row);
+                Ok(())
+            }
+            None => Err(AppError::NoMatch(search.to_string())),
+        }
+    }
+
+    /// The rows of the value view as text, with the row scrolling to each is. Collections only
+    /// have the members and elements loaded so far.
+    fn text_rows(&mut self) -> Vec<(usize, String)> {
+        if let Some(nodes) = self.tree_nodes() {
+            return nodes.iter().map(TreeNode::text).enumerate().collect();
+        }
+
+        let (wraps, width) = (self.wraps(), self.view_width);
+        let Some(viewed) = self.viewing_value.as_mut() else { return Vec::new() };
+        if wraps {
+            // A wrapped line takes several rows of the view
+            let rows = viewed.wrapped_rows(width).to_vec();
+            return viewed.text_lines().zip(rows).map(|(line, row)| (row, line.to_string())).collect();
+        }
+
+        match &viewed.value {
+            KeyValue::String(_) => viewed.text_lines().map(str::to_string).enumerate().collect(),
+            KeyValue::Hash(fields) => fields.iter()
+                .map(|(field, value)| format!("{} {}", escape(field), escape(value)))
+                .enumerate()
+                .collect(),
+            KeyValue::List(window) => window.elements.iter()
+                .enumerate()
+                .map(|(i, element)| (window.start + i, element.clone()))
+                .collect(),
+            KeyValue::Set(members) => members.iter().map(|member| escape(member)).enumerate().collect(),
+            KeyValue::ZSet(members) => members.iter()
+                .map(|(member, score)| format!("{} {}", escape(member), score))
+                .enumerate()
+                .collect(),
+            KeyValue::Stream(stream) => stream.entries.iter()
+                .map(|entry| format!("{} {}", entry.id, entry.fields_as_text()))
+                .enumerate()
+                .collect(),
+        }
+    }
+
+    /// Whether the viewed string is shown wrapped, it does not scroll sideways then.
+    pub fn wraps(&self) -> bool {
+        let is_string = self.viewing_value.as_ref().is_some_and(|value| matches!(value.value, KeyValue::String(_)));
+        self.wrap && is_string && self.tree_nodes().is_none()
+    }
+
+    /// Turns wrapping the viewed string on or off, staying on the line at the top of the view.
+    pub fn toggle_wrap(&mut self) {
+        let line = self.text_rows().iter().rposition(|(row, _)| *row <= self.viewing_key_scroll);
+        self.wrap = !self.wrap;
+        self.viewing_key_hscroll = 0;
+        if let Some((row, _)) = line.and_then(|line| self.text_rows().into_iter().nth(line)) {
+            self.viewing_key_scroll = row;
+        }
+    }
+
+    /// Scrolls the value view sideways, no further than its longest line.
+    pub fn scroll_horizontally(&mut self, right: bool) {
+        if self.wraps() {
+            return;
+        }
+
+        let widest = self.text_rows().iter().map(|(_, text)| text.chars().count()).max().unwrap_or(0);
+        self.viewing_key_hscroll = if right {
+            (self.viewing_key_hscroll + HSCROLL_STEP).min(widest.saturating_sub(1))
+        } else {
+            self.viewing_key_hscroll.saturating_sub(HSCROLL_STEP)
+        };
+    }
+
+    /// Expands or collapses the selected object or array of the viewed document tree.
+    pub fn toggle_node(&mut self) {
+        let Some(nodes) = self.tree_nodes() else { return };
+        let Some(node) = nodes.get(self.viewing_key_scroll) else { return };
+        if !(node.value.is_object() || node.value.is_array()) {
+            return;
+        }
+
+        let path = node.path.clone();
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+    }
+
+    /// Scrolls a list to its last screen of elements.
+    pub fn scroll_to_end(&mut self) {
+        if let Some(KeyValue::List(window)) = self.viewing_value.as_ref().map(|value| &value.value) {
+            self.viewing_key_scroll = window.len.saturating_sub(self.page_height as usize);
+        }
+    }
+
+    /// Scrolls a list to the element at `index`, counted from the tail when negative like LRANGE
+    /// indices are.
+    pub fn jump_to_index(&mut self, index: &str) -> Result<(), AppError> {
+        let Some(KeyValue::List(window)) = self.viewing_value.as_ref().map(|value| &value.value) else {
+            return Ok(());
+        };
+        let Some(element) = window.index(index) else {
+            return Err(AppError::InvalidIndex { index: index.to_string(), len: window.len });
+        };
+
+        self.scroll_to(element);
+        Ok(())
+    }
+
+    pub fn cycle_auto_refresh(&mut self) {
+        let index = AUTO_REFRESH.iter().position(|interval| *interval == self.auto_refresh).unwrap_or(0);
+        self.auto_refresh = AUTO_REFRESH[(index + 1) % AUTO_REFRESH.len()];
+    }
+
+    /// Refreshes the viewed value once the auto refresh interval has passed.
+    pub fn on_tick(&mut self) {
+        let (Some(interval), Some(value)) = (self.auto_refresh, &self.viewing_value) else { return };
+        if value.fetched_at.elapsed() >= interval {
+            self.refresh_value();
+        }
+    }
+
+    pub fn close_view(&mut self) {
+        self.pending.retain(|(_, job)| {
+            !matches!(job, Job::Value | Job::Members | Job::List | Job::Entries | Job::Groups | Job::Pending)
+        });
+        self.viewing_key = None;
+        self.viewing_value = None;
+    }
+
+    fn apply_value(&mut self, key: Vec<u8>, value: RedisResult<Option<ViewedValue>>) -> Result<(), AppError> {
+        if self.viewing_key.as_ref() != Some(&key) {
+            return Ok(());
+        }
+
+        match value {
+            Ok(Some(value)) => {
+                self.viewing_value = Some(value);
+                self.update_hex_dump();
+                self.scroll_to(self.viewing_key_scroll);
+                Ok(())
+            }
+            Ok(None) => {
+                self.close_view();
+                Err(AppError::MissingKey(key))
+            }
+            Err(source) => {
+                // Back to the key list, the value cannot be shown
+                self.close_view();
+                Err(AppError::ReadKey { key, source })
+            }
+        }
+    }
+
+    /// Asks for the metadata of the viewed key, or the one selected in the key list, shown in
+    /// the inspector once it arrives.
+    pub fn request_key_info(&mut self) {
+        let key = match &self.viewing_key {
+            Some(key) => Some(key.clone()),
+            None => self.table_state.selected()
+                .and_then(|selected| self.iter_keys().nth(selected))
+                .map(|(key, _)| key.clone()),
+        };
+        if let Some(key) = key.filter(|_| !self.is_pending(|job| *job == Job::Inspect)) {
+            self.request(Job::Inspect, RedisRequest::Inspect(key));
+        }
+    }
+
+    /// Keeps the type and size of an inspected key for the key list.
+    fn remember_info(&mut self, info: &KeyInfo) {
+        if let Some(meta) = self.keys.get_mut(&info.key) {
+            meta._type = Some(info.value_type.clone());
+            meta.size = info.memory;
+        }
+    }
+
+    /// Asks for the consumer groups of the viewed stream, shown once they arrive.
+    pub fn request_groups(&mut self) {
+        let Some(key) = self.viewing_key.clone() else { return };
+        let is_stream = self.viewing_value.as_ref().is_some_and(|value| matches!(value.value, KeyValue::Stream(_)));
+        if is_stream && !self.is_pending(|job| *job == Job::Groups) {
+            self.request(Job::Groups, RedisRequest::Groups(key));
+        }
+    }
+
+    pub fn request_pending(&mut self, key: Vec<u8>, group: String) {
+        self.pending.retain(|(_, job)| *job != Job::Pending);
+        self.request(Job::Pending, RedisRequest::Pending { key, group });
+    }
+
+    fn apply_entries(&mut self, key: Vec<u8>, page: RedisResult<EntryPage>) -> Result<(), AppError> {
+        let Some(value) = self.viewing_value.as_mut().filter(|_| self.viewing_key.as_ref() == Some(&key)) else {
+            return Ok(());
+        };
+        let KeyValue::Stream(stream) = &mut value.value else { return Ok(()) };
+
+        match page {
+            Ok(page) => {
+                stream.entries.extend(page.entries);
+                stream.next = page.next;
+                Ok(())
+            }
+            Err(source) => {
+                // Keep the entries read so far, paging stops until the value is refreshed
+                stream.next = None;
+                Err(AppError::ReadKey { key, source })
+            }
+        }
+    }
+
+    fn apply_list(&mut self, key: Vec<u8>, window: RedisResult<ListWindow>) -> Result<(), AppError> {
+        let Some(value) = self.viewing_value.as_mut().filter(|_| self.viewing_key.as_ref() == Some(&key)) else {
+            return Ok(());
+        };
+
+        match window {
+            Ok(window) if window.len == 0 => {
+                self.close_view();
+                Err(AppError::MissingKey(key))
+            }
+            Ok(window) => {
+                value.value = KeyValue::List(window);
+                // The list may have shrunk since the view was scrolled
+                self.scroll_to(self.viewing_key_scroll);
+                Ok(())
+            }
+            Err(source) => {
+                self.close_view();
+                Err(AppError::ReadKey { key, source })
+            }
+        }
+    }
+
+    fn apply_members(&mut self, key: Vec<u8>, page: RedisResult<MemberPage>) -> Result<(), AppError> {
+        let Some(value) = self.viewing_value.as_mut().filter(|_| self.viewing_key.as_ref() == Some(&key)) else {
+            return Ok(());
+        };
+
+        match page {
+            Ok(page) => {
+                value.apply_members(page);
+                Ok(())
+            }
+            Err(source) => {
+                // Keep the members read so far, paging stops until the value is refreshed
+                if let Some(members) = value.members.as_mut() {
+                    members.done = true;
+                }
+                Err(AppError::ReadKey { key, source })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keyspace_info() {
+        let info = "# Keyspace\r\ndb0:keys=12,expires=3,avg_ttl=60000\r\ndb5:keys=1,expires=0,avg_ttl=0\r\n";
 
-        return result.ok();
+        assert_eq!(parse_keyspace_info(info), vec![
+            KeyspaceInfo { db: 0, keys: 12, expires: 3, avg_ttl: 60000 },
+            KeyspaceInfo { db: 5, keys: 1, expires: 0, avg_ttl: 0 },
+        ]);
     }
 }
@@ -0,0 +1,232 @@
+use std::time::Duration;
+use redis::{
+    self,
+    cluster::ClusterConnection,
+    cluster_routing::{RoutingInfo, SingleNodeRoutingInfo},
+    sentinel::{Sentinel, SentinelNodeConnectionInfo},
+    Cmd,
+    ConnectionInfo,
+    ConnectionLike,
+    ErrorKind,
+    RedisError,
+    RedisResult,
+    Value,
+};
+
+/// Address of a cluster node.
+pub type Node = (String, u16);
+
+/// The connection a session runs its commands on. Key commands sent to a cluster are routed to
+/// the node owning the key's slot, so callers can `query` either variant the same way.
+pub enum SessionConnection {
+    Single(redis::Connection),
+    Cluster(Box<ClusterConnection>),
+    Sentinel(Box<SentinelConnection>),
+}
+
+impl SessionConnection {
+    /// Runs `cmd` on the node at `node`, or on the single connection when `node` is `None`.
+    pub fn query_node<T: redis::FromRedisValue>(&mut self, cmd: &Cmd, node: Option<&Node>) -> RedisResult<T> {
+        match (self, node) {
+            (SessionConnection::Cluster(con), Some((host, port))) => {
+                let routing = RoutingInfo::SingleNode(SingleNodeRoutingInfo::ByAddress {
+                    host: host.clone(),
+                    port: *port,
+                });
+                redis::from_owned_redis_value(con.route_command(cmd, routing)?)
+            }
+            (con, _) => cmd.query(con),
+        }
+    }
+
+    /// Remembers the selected db so a connection rediscovered after a failover selects it again.
+    pub fn set_db(&mut self, db: i64) {
+        if let SessionConnection::Sentinel(con) = self {
+            con.set_db(db);
+        }
+    }
+
+    /// Addresses of every master, or `None` for a single server.
+    pub fn masters(&mut self, seed_host: &str) -> RedisResult<Option<Vec<Node>>> {
+        let SessionConnection::Cluster(con) = self else {
+            return Ok(None);
+        };
+
+        let routing = RoutingInfo::SingleNode(SingleNodeRoutingInfo::Random);
+        let slots = con.route_command(redis::cmd("CLUSTER").arg("SLOTS"), routing)?;
+        Ok(Some(parse_cluster_slots(slots, seed_host)?))
+    }
+}
+
+/// Collects the unique master addresses from a `CLUSTER SLOTS` reply, where each entry looks like
+/// `[start, end, [host, port, id, ...], replicas...]`. Nodes announcing no host share the seed's.
+fn parse_cluster_slots(slots: Value, seed_host: &str) -> RedisResult<Vec<Node>> {
+    let invalid = || RedisError::from((ErrorKind::TypeError, "Unexpected CLUSTER SLOTS reply"));
+    let Value::Array(ranges) = slots else { return Err(invalid()) };
+
+    let mut masters = Vec::new();
+    for range in ranges {
+        let Value::Array(range) = range else { return Err(invalid()) };
+        let Some(Value::Array(master)) = range.get(2) else { return Err(invalid()) };
+
+        let host: String = redis::from_redis_value(master.first().ok_or_else(invalid)?)?;
+        let port: u16 = redis::from_redis_value(master.get(1).ok_or_else(invalid)?)?;
+        let host = if host.is_empty() || host == "?" { seed_host.to_string() } else { host };
+
+        if !masters.contains(&(host.clone(), port)) {
+            masters.push((host, port));
+        }
+    }
+
+    Ok(masters)
+}
+
+/// A connection to the node a set of sentinels announces for a master name. When the node goes
+/// away or is demoted, the sentinels are asked again and the failed command is retried once.
+pub struct SentinelConnection {
+    sentinel: Sentinel,
+    master_name: String,
+    node_info: SentinelNodeConnectionInfo,
+    read_replica: bool,
+    con: redis::Connection,
+}
+
+impl SentinelConnection {
+    pub fn connect(
+        sentinels: Vec<ConnectionInfo>,
+        master_name: String,
+        node_info: SentinelNodeConnectionInfo,
+        read_replica: bool,
+    ) -> RedisResult<SentinelConnection> {
+        let mut sentinel = Sentinel::build(sentinels)?;
+        let con = discover(&mut sentinel, &master_name, &node_info, read_replica)?;
+
+        Ok(SentinelConnection { sentinel, master_name, node_info, read_replica, con })
+    }
+
+    fn set_db(&mut self, db: i64) {
+        if let Some(redis) = self.node_info.redis_connection_info.as_mut() {
+            redis.db = db;
+        }
+    }
+
+    fn with_failover<T>(&mut self, mut f: impl FnMut(&mut redis::Connection) -> RedisResult<T>) -> RedisResult<T> {
+        match f(&mut self.con) {
+            Err(err) if is_failover(&err) => {
+                self.rediscover()?;
+                f(&mut self.con)
+            }
+            result => result,
+        }
+    }
+
+    fn rediscover(&mut self) -> RedisResult<()> {
+        self.con = discover(&mut self.sentinel, &self.master_name, &self.node_info, self.read_replica)?;
+        Ok(())
+    }
+}
+
+fn discover(
+    sentinel: &mut Sentinel,
+    master_name: &str,
+    node_info: &SentinelNodeConnectionInfo,
+    read_replica: bool,
+) -> RedisResult<redis::Connection> {
+    let client = if read_replica {
+        sentinel.replica_for(master_name, Some(node_info))?
+    } else {
+        sentinel.master_for(master_name, Some(node_info))?
+    };
+
+    client.get_connection_with_timeout(Duration::from_secs(5))
+}
+
+/// Errors seen while a master is failing over: the node is gone, or it was demoted to a replica.
+fn is_failover(err: &RedisError) -> bool {
+    err.is_io_error()
+        || err.is_connection_dropped()
+        || err.is_connection_refusal()
+        || matches!(err.kind(), ErrorKind::ReadOnly | ErrorKind::MasterDown)
+}
+
+impl ConnectionLike for SessionConnection {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
+        match self {
+            SessionConnection::Single(con) => con.req_packed_command(cmd),
+            SessionConnection::Cluster(con) => con.req_packed_command(cmd),
+            SessionConnection::Sentinel(con) => con.with_failover(|con| con.req_packed_command(cmd)),
+        }
+    }
+
+    fn req_packed_commands(&mut self, cmd: &[u8], offset: usize, count: usize) -> RedisResult<Vec<Value>> {
+        match self {
+            SessionConnection::Single(con) => con.req_packed_commands(cmd, offset, count),
+            SessionConnection::Cluster(con) => con.req_packed_commands(cmd, offset, count),
+            SessionConnection::Sentinel(con) => con.with_failover(|con| con.req_packed_commands(cmd, offset, count)),
+        }
+    }
+
+    fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        match self {
+            SessionConnection::Single(con) => con.req_command(cmd),
+            SessionConnection::Cluster(con) => con.req_command(cmd),
+            SessionConnection::Sentinel(con) => con.with_failover(|con| con.req_command(cmd)),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            SessionConnection::Single(con) => con.get_db(),
+            SessionConnection::Cluster(con) => con.get_db(),
+            SessionConnection::Sentinel(con) => con.con.get_db(),
+        }
+    }
+
+    fn supports_pipelining(&self) -> bool {
+        match self {
+            SessionConnection::Single(con) => con.supports_pipelining(),
+            SessionConnection::Cluster(con) => con.supports_pipelining(),
+            SessionConnection::Sentinel(con) => con.con.supports_pipelining(),
+        }
+    }
+
+    fn check_connection(&mut self) -> bool {
+        match self {
+            SessionConnection::Single(con) => con.check_connection(),
+            SessionConnection::Cluster(con) => con.check_connection(),
+            SessionConnection::Sentinel(con) => con.con.check_connection() || con.rediscover().is_ok(),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        match self {
+            SessionConnection::Single(con) => con.is_open(),
+            SessionConnection::Cluster(con) => con.is_open(),
+            SessionConnection::Sentinel(con) => con.con.is_open(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn parses_unique_masters_from_cluster_slots() {
+        let node = |host: &str, port: i64| Value::Array(vec![bulk(host), Value::Int(port), bulk("id")]);
+        let slots = Value::Array(vec![
+            Value::Array(vec![Value::Int(0), Value::Int(5460), node("10.0.0.1", 7000), node("10.0.0.4", 7003)]),
+            Value::Array(vec![Value::Int(5461), Value::Int(10922), node("", 7001)]),
+            Value::Array(vec![Value::Int(10923), Value::Int(12000), node("10.0.0.1", 7000)]),
+        ]);
+
+        assert_eq!(parse_cluster_slots(slots, "seed").expect("to parse"), vec![
+            ("10.0.0.1".to_string(), 7000),
+            ("seed".to_string(), 7001),
+        ]);
+    }
+}
@@ -0,0 +1,401 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use thiserror::Error;
+
+mod v1_0;
+mod v1_1;
+mod v1_2;
+mod v1_3;
+
+pub use v1_0::DB_V1_0;
+pub use v1_1::DB_V1_1;
+pub use v1_2::DB_V1_2;
+pub use v1_3::{DB_V1_3, RedisServerConf, AddressConf, SentinelAddrConf, AuthConf, TlsConf};
+
+/// The config version the app reads and writes. Older files are migrated up to it on load.
+#[allow(non_camel_case_types)]
+pub type DB_LATEST = DB_V1_3;
+
+#[derive(Error, Debug)]
+pub enum DBError {
+    #[error("no config directory found for this platform")]
+    NoConfigDir,
+    #[error("could not access config file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not parse config file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DBVersions {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+}
+
+impl DBVersions {
+    pub const LATEST: DBVersions = DBVersions::V1_3;
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: DBVersions,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DB {
+    DB_V1_0(DB_V1_0),
+    DB_V1_1(DB_V1_1),
+    DB_V1_2(DB_V1_2),
+    DB_V1_3(DB_V1_3),
+}
+
+impl Default for DB {
+    fn default() -> Self {
+        DB::DB_V1_3(DB_V1_3 {
+            version: DBVersions::V1_3,
+            server_configs: Vec::new(),
+            display_rules: Vec::new(),
+        })
+    }
+}
+
+impl From<DB_LATEST> for DB {
+    fn from(db: DB_LATEST) -> Self {
+        DB::DB_V1_3(db)
+    }
+}
+
+impl DB {
+    /// `$XDG_CONFIG_HOME/rudis-tui/db.json` on linux, the platform equivalent elsewhere.
+    pub fn default_path() -> Result<PathBuf, DBError> {
+        dirs::config_dir()
+            .map(|dir| dir.join("rudis-tui").join("db.json"))
+            .ok_or(DBError::NoConfigDir)
+    }
+
+    /// Parses a config of any known version, picking the struct from its `version` field.
+    pub fn parse(content: &str) -> Result<DB, DBError> {
+        let value: Value = serde_json::from_str(content)?;
+        let VersionProbe { version } = serde_json::from_value(value.clone())?;
+
+        Ok(match version {
+            DBVersions::V1_0 => DB::DB_V1_0(serde_json::from_value(value)?),
+            DBVersions::V1_1 => DB::DB_V1_1(serde_json::from_value(value)?),
+            DBVersions::V1_2 => DB::DB_V1_2(serde_json::from_value(value)?),
+            DBVersions::V1_3 => DB::DB_V1_3(serde_json::from_value(value)?),
+        })
+    }
+
+    pub fn version(&self) -> DBVersions {
+        match self {
+            DB::DB_V1_0(_) => DBVersions::V1_0,
+            DB::DB_V1_1(_) => DBVersions::V1_1,
+            DB::DB_V1_2(_) => DBVersions::V1_2,
+            DB::DB_V1_3(_) => DBVersions::V1_3,
+        }
+    }
+
+    /// Runs a single migration step, returning `self` unchanged when already at the latest version.
+    fn migrate(self) -> DB {
+        match self {
+            DB::DB_V1_0(db) => DB::DB_V1_1(db.into()),
+            DB::DB_V1_1(db) => DB::DB_V1_2(db.into()),
+            DB::DB_V1_2(db) => DB::DB_V1_3(db.into()),
+            DB::DB_V1_3(_) => self,
+        }
+    }
+
+    pub fn into_latest(self) -> DB_LATEST {
+        let mut db = self;
+        loop {
+            match db {
+                DB::DB_V1_3(latest) => return latest,
+                _ => db = db.migrate(),
+            }
+        }
+    }
+
+    /// Loads the config at `path` and migrates it to the latest version. When a migration was
+    /// needed the original file is copied to `<path>.<version>.bak` before it is rewritten.
+    pub fn load(path: &Path) -> Result<DB_LATEST, DBError> {
+        let db = DB::parse(&fs::read_to_string(path)?)?;
+        let version = db.version();
+
+        if version == DBVersions::LATEST {
+            return Ok(db.into_latest());
+        }
+
+        // The config holds passwords, the backup is only readable by the user like the config
+        DB::write_private(&DB::sibling_path(path, &format!("{:?}.bak", version)), &fs::read(path)?)?;
+        let latest = db.into_latest();
+        DB::from(latest.clone()).save(path)?;
+        Ok(latest)
+    }
+
+    /// Loads the config at `path`, writing an empty one first if it does not exist yet.
+    pub fn load_or_create(path: &Path) -> Result<DB_LATEST, DBError> {
+        if !path.exists() {
+            let db = DB::default();
+            db.save(path)?;
+            return Ok(db.into_latest());
+        }
+
+        DB::load(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), DBError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        DB::write_private(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Writes `contents` to `path` through a temporary sibling file renamed over it, so a crash
+    /// never leaves a truncated config. On unix the file is only readable by the user.
+    fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+        let temp_path = DB::sibling_path(path, "tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            let file = options.open(&temp_path)?;
+            // The mode only applies to a new file, not to a temporary one left by a crash
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+            DB::write_synced(file, contents)?;
+        }
+        #[cfg(not(unix))]
+        DB::write_synced(options.open(&temp_path)?, contents)?;
+        fs::rename(temp_path, path)
+    }
+
+    fn write_synced(mut file: File, contents: &[u8]) -> io::Result<()> {
+        file.write_all(contents)?;
+        file.sync_all()
+    }
+
+    /// `<path>.<extension>`
+    fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+        let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!(".{}", extension));
+        path.with_file_name(file_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(db: DB) {
+        let json = serde_json::to_string_pretty(&db).expect("to serialize");
+        let parsed = DB::parse(&json).expect("to parse");
+        assert_eq!(parsed.version(), db.version());
+        assert_eq!(parsed, db);
+    }
+
+    #[test]
+    fn round_trip_v1_0() {
+        round_trip(DB::DB_V1_0(DB_V1_0 {
+            version: DBVersions::V1_0,
+            server_configs: vec![v1_0::RedisServerConf {
+                name: "local".to_string(),
+                host: "localhost".to_string(),
+                port: 6379,
+                username: Some("app".to_string()),
+                password: Some("secret".to_string()),
+                db: 2,
+                tls: true,
+            }],
+        }));
+    }
+
+    #[test]
+    fn round_trip_v1_1() {
+        round_trip(DB::DB_V1_1(DB_V1_1 {
+            version: DBVersions::V1_1,
+            server_configs: vec![v1_1::RedisServerConf {
+                name: "local".to_string(),
+                host: "localhost".to_string(),
+                port: 6379,
+                auth: Some(AuthConf {
+                    username: Some("app".to_string()),
+                    password: "secret".to_string(),
+                }),
+                db: 2,
+                tls: true,
+                color: Some("#ff8800".to_string()),
+                read_only: true,
+            }],
+        }));
+    }
+
+    #[test]
+    fn round_trip_v1_2() {
+        round_trip(DB::DB_V1_2(DB_V1_2 {
+            version: DBVersions::V1_2,
+            server_configs: vec![v1_2::RedisServerConf {
+                name: "local".to_string(),
+                host: "localhost".to_string(),
+                port: 6380,
+                auth: None,
+                db: 0,
+                tls: Some(TlsConf {
+                    ca_cert: Some(PathBuf::from("/etc/redis/ca.pem")),
+                    client_cert: Some(PathBuf::from("/etc/redis/client.pem")),
+                    client_key: Some(PathBuf::from("/etc/redis/client.key")),
+                    insecure: true,
+                }),
+                color: None,
+                read_only: false,
+            }],
+        }));
+    }
+
+    #[test]
+    fn round_trip_v1_3() {
+        round_trip(DB::DB_V1_3(DB_V1_3 {
+            version: DBVersions::V1_3,
+            server_configs: vec![
+                RedisServerConf {
+                    name: "remote".to_string(),
+                    address: AddressConf::Tcp { host: "localhost".to_string(), port: 6379 },
+                    auth: None,
+                    db: 0,
+                    tls: Some(TlsConf::default()),
+                    color: None,
+                    read_only: false,
+                    cluster: true,
+                },
+                RedisServerConf {
+                    name: "socket".to_string(),
+                    address: AddressConf::Unix { path: PathBuf::from("/run/redis/redis.sock") },
+                    auth: None,
+                    db: 3,
+                    tls: None,
+                    color: Some("green".to_string()),
+                    read_only: true,
+                    cluster: false,
+                },
+                RedisServerConf {
+                    name: "sentinel".to_string(),
+                    address: AddressConf::Sentinel {
+                        master_name: "mymaster".to_string(),
+                        sentinels: vec![
+                            SentinelAddrConf { host: "10.0.0.1".to_string(), port: 26379 },
+                            SentinelAddrConf { host: "10.0.0.2".to_string(), port: 26380 },
+                        ],
+                        read_replica: true,
+                    },
+                    auth: None,
+                    db: 1,
+                    tls: None,
+                    color: None,
+                    read_only: false,
+                    cluster: false,
+                },
+            ],
+            display_rules: vec!["session:* => msgpack".to_string(), "metrics:* => hex".to_string()],
+        }));
+    }
+
+    #[test]
+    fn parses_minimal_v1_0() {
+        let db = DB::parse(r#"{
+            "version": "V1_0",
+            "server_configs": [{ "name": "local", "host": "localhost", "port": 6379 }]
+        }"#).expect("to parse");
+
+        assert_eq!(db.version(), DBVersions::V1_0);
+    }
+
+    #[test]
+    fn migrates_v1_0_to_latest() {
+        let db = DB::DB_V1_0(DB_V1_0 {
+            version: DBVersions::V1_0,
+            server_configs: vec![
+                v1_0::RedisServerConf {
+                    name: "acl".to_string(),
+                    host: "localhost".to_string(),
+                    port: 6379,
+                    username: Some("app".to_string()),
+                    password: Some("secret".to_string()),
+                    db: 1,
+                    tls: false,
+                },
+                v1_0::RedisServerConf {
+                    name: "open".to_string(),
+                    host: "localhost".to_string(),
+                    port: 6380,
+                    username: Some("ignored".to_string()),
+                    password: None,
+                    db: 0,
+                    tls: true,
+                },
+            ],
+        });
+
+        let latest = db.into_latest();
+
+        assert_eq!(latest.version, DBVersions::LATEST);
+        assert_eq!(latest.server_configs[0].auth, Some(AuthConf {
+            username: Some("app".to_string()),
+            password: "secret".to_string(),
+        }));
+        assert_eq!(latest.server_configs[0].db, 1);
+        assert_eq!(latest.server_configs[1].address, AddressConf::Tcp {
+            host: "localhost".to_string(),
+            port: 6380,
+        });
+        assert_eq!(latest.server_configs[1].auth, None);
+        assert_eq!(latest.server_configs[0].tls, None);
+        assert_eq!(latest.server_configs[1].tls, Some(TlsConf::default()));
+    }
+
+    #[test]
+    fn load_backs_up_and_rewrites_old_versions() {
+        let dir = tempfile::tempdir().expect("to create temp dir");
+        let path = dir.path().join("db.json");
+        let original = r#"{ "version": "V1_0", "server_configs": [] }"#;
+        fs::write(&path, original).expect("to write config");
+
+        let db = DB::load(&path).expect("to load");
+
+        assert_eq!(db.version, DBVersions::LATEST);
+        let backup = fs::read_to_string(dir.path().join("db.json.V1_0.bak")).expect("to read backup");
+        assert_eq!(backup, original);
+        let rewritten = DB::parse(&fs::read_to_string(&path).expect("to read config")).expect("to parse");
+        assert_eq!(rewritten.version(), DBVersions::LATEST);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for file in [path, dir.path().join("db.json.V1_0.bak")] {
+                let mode = fs::metadata(&file).expect("to stat").permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+            }
+            assert!(!dir.path().join("db.json.tmp").exists());
+        }
+    }
+
+    #[test]
+    fn load_or_create_writes_default() {
+        let dir = tempfile::tempdir().expect("to create temp dir");
+        let path = dir.path().join("rudis-tui").join("db.json");
+
+        let db = DB::load_or_create(&path).expect("to create");
+
+        assert!(path.exists());
+        assert!(db.server_configs.is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Serialize, Deserialize};
+
+use super::DBVersions;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedisServerConf {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub db: i64,
+    #[serde(default)]
+    pub tls: bool,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DB_V1_0 {
+    pub version: DBVersions,
+    pub server_configs: Vec<RedisServerConf>
+}
@@ -0,0 +1,65 @@
+use serde::{Serialize, Deserialize};
+
+use super::{DBVersions, v1_0};
+
+/// Credentials sent with AUTH. `username` is only needed for Redis 6+ ACL users.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuthConf {
+    #[serde(default)]
+    pub username: Option<String>,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedisServerConf {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub auth: Option<AuthConf>,
+    #[serde(default)]
+    pub db: i64,
+    #[serde(default)]
+    pub tls: bool,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DB_V1_1 {
+    pub version: DBVersions,
+    pub server_configs: Vec<RedisServerConf>
+}
+
+impl From<v1_0::RedisServerConf> for RedisServerConf {
+    fn from(conf: v1_0::RedisServerConf) -> Self {
+        // A username without a password could never authenticate, so it is dropped
+        let auth = conf.password.map(|password| AuthConf {
+            username: conf.username,
+            password,
+        });
+
+        RedisServerConf {
+            name: conf.name,
+            host: conf.host,
+            port: conf.port,
+            auth,
+            db: conf.db,
+            tls: conf.tls,
+            color: None,
+            read_only: false,
+        }
+    }
+}
+
+impl From<v1_0::DB_V1_0> for DB_V1_1 {
+    fn from(db: v1_0::DB_V1_0) -> Self {
+        DB_V1_1 {
+            version: DBVersions::V1_1,
+            server_configs: db.server_configs.into_iter().map(RedisServerConf::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+
+use super::{DBVersions, v1_1};
+
+pub use v1_1::AuthConf;
+
+/// PEM files used for `rediss://` connections. Without a CA the system trust store is used.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TlsConf {
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
+    #[serde(default)]
+    pub client_cert: Option<PathBuf>,
+    #[serde(default)]
+    pub client_key: Option<PathBuf>,
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedisServerConf {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub auth: Option<AuthConf>,
+    #[serde(default)]
+    pub db: i64,
+    #[serde(default)]
+    pub tls: Option<TlsConf>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DB_V1_2 {
+    pub version: DBVersions,
+    pub server_configs: Vec<RedisServerConf>
+}
+
+impl From<v1_1::RedisServerConf> for RedisServerConf {
+    fn from(conf: v1_1::RedisServerConf) -> Self {
+        RedisServerConf {
+            name: conf.name,
+            host: conf.host,
+            port: conf.port,
+            auth: conf.auth,
+            db: conf.db,
+            tls: conf.tls.then(TlsConf::default),
+            color: conf.color,
+            read_only: conf.read_only,
+        }
+    }
+}
+
+impl From<v1_1::DB_V1_1> for DB_V1_2 {
+    fn from(db: v1_1::DB_V1_1) -> Self {
+        DB_V1_2 {
+            version: DBVersions::V1_2,
+            server_configs: db.server_configs.into_iter().map(RedisServerConf::from).collect(),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+
+use super::{DBVersions, v1_2};
+
+pub use v1_2::{AuthConf, TlsConf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressConf {
+    Tcp { host: String, port: u16 },
+    Unix { path: PathBuf },
+    /// The master named `master_name` as announced by any of `sentinels`, or one of its replicas.
+    Sentinel {
+        master_name: String,
+        sentinels: Vec<SentinelAddrConf>,
+        #[serde(default)]
+        read_replica: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SentinelAddrConf {
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RedisServerConf {
+    pub name: String,
+    pub address: AddressConf,
+    #[serde(default)]
+    pub auth: Option<AuthConf>,
+    #[serde(default)]
+    pub db: i64,
+    #[serde(default)]
+    pub tls: Option<TlsConf>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub cluster: bool,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DB_V1_3 {
+    pub version: DBVersions,
+    pub server_configs: Vec<RedisServerConf>,
+    /// `pattern => display` rules picking how the values of matching keys are shown, like
+    /// `session:* => msgpack`.
+    #[serde(default)]
+    pub display_rules: Vec<String>,
+}
+
+impl From<v1_2::RedisServerConf> for RedisServerConf {
+    fn from(conf: v1_2::RedisServerConf) -> Self {
+        RedisServerConf {
+            name: conf.name,
+            address: AddressConf::Tcp { host: conf.host, port: conf.port },
+            auth: conf.auth,
+            db: conf.db,
+            tls: conf.tls,
+            color: conf.color,
+            read_only: conf.read_only,
+            cluster: false,
+        }
+    }
+}
+
+impl From<v1_2::DB_V1_2> for DB_V1_3 {
+    fn from(db: v1_2::DB_V1_2) -> Self {
+        DB_V1_3 {
+            version: DBVersions::V1_3,
+            server_configs: db.server_configs.into_iter().map(RedisServerConf::from).collect(),
+            display_rules: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use std::io::Read;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use serde_json::{Map, Value};
+
+use crate::value::{escape, escape_lines, is_binary};
+
+/// Most bytes a compressed value is inflated to.
+const MAX_DECODED: u64 = 64 * 1024 * 1024;
+
+/// Most decoders detected on top of one another.
+const MAX_DEPTH: usize = 4;
+
+const PADDING: GeneralPurposeConfig = GeneralPurposeConfig::new()
+    .with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, PADDING);
+const BASE64_URL: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, PADDING);
+
+/// A step from a stored string towards readable data: an encoding or a compression that yields
+/// bytes, or a serialization format that yields a document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Decoder {
+    Base64,
+    Gzip,
+    Zlib,
+    Zstd,
+    Lz4,
+    MsgPack,
+    Cbor,
+    Json,
+}
+
+impl Decoder {
+    pub const ALL: [Decoder; 8] = [
+        Decoder::Base64,
+        Decoder::Gzip,
+        Decoder::Zlib,
+        Decoder::Zstd,
+        Decoder::Lz4,
+        Decoder::MsgPack,
+        Decoder::Cbor,
+        Decoder::Json,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Decoder::Base64 => "base64",
+            Decoder::Gzip => "gzip",
+            Decoder::Zlib => "zlib",
+            Decoder::Zstd => "zstd",
+            Decoder::Lz4 => "lz4",
+            Decoder::MsgPack => "msgpack",
+            Decoder::Cbor => "cbor",
+            Decoder::Json => "json",
+        }
+    }
+
+    /// Whether this decoder reads a document rather than bytes, it ends a chain.
+    fn is_format(&self) -> bool {
+        matches!(self, Decoder::MsgPack | Decoder::Cbor | Decoder::Json)
+    }
+
+    /// Decodes or inflates `bytes`.
+    fn unwrap(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        let read = match self {
+            Decoder::Base64 => return decode_base64(bytes),
+            Decoder::Gzip => MultiGzDecoder::new(bytes).take(MAX_DECODED + 1).read_to_end(&mut out),
+            Decoder::Zlib => ZlibDecoder::new(bytes).take(MAX_DECODED + 1).read_to_end(&mut out),
+            Decoder::Zstd => ruzstd::StreamingDecoder::new(bytes)
+                .map_err(|err| err.to_string())?
+                .take(MAX_DECODED + 1)
+                .read_to_end(&mut out),
+            // Frames have a magic number, raw blocks are usually stored after their length
+            Decoder::Lz4 if bytes.starts_with(&[0x04, 0x22, 0x4d, 0x18]) => {
+                lz4_flex::frame::FrameDecoder::new(bytes).take(MAX_DECODED + 1).read_to_end(&mut out)
+            }
+            Decoder::Lz4 => {
+                // The length is checked before it is allocated, any 4 bytes read as one
+                let (size, block) = lz4_flex::block::uncompressed_size(bytes).map_err(|err| err.to_string())?;
+                if size as u64 > MAX_DECODED {
+                    return Err(format!("decompressed size of {} bytes is too large", size));
+                }
+                return lz4_flex::block::decompress(block, size).map_err(|err| err.to_string());
+            }
+            Decoder::MsgPack | Decoder::Cbor | Decoder::Json => unreachable!("formats are read, not unwrapped"),
+        };
+
+        // One byte past the cap tells a value decoding to more from one decoding to exactly it
+        match read {
+            Ok(_) if out.len() as u64 > MAX_DECODED => {
+                Err(format!("decompressed size exceeds {} bytes, it is too large", MAX_DECODED))
+            }
+            Ok(_) => Ok(out),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    /// Reads the document in `bytes`, which must hold nothing else.
+    fn read(&self, bytes: &[u8]) -> Result<Value, String> {
+        let mut rest = bytes;
+        let value = match self {
+            Decoder::MsgPack => rmpv::decode::read_value(&mut rest)
+                .map(msgpack_to_json)
+                .map_err(|err| err.to_string())?,
+            Decoder::Cbor => ciborium::from_reader::<ciborium::Value, _>(&mut rest)
+                .map(cbor_to_json)
+                .map_err(|err| err.to_string())?,
+            _ => return serde_json::from_slice(bytes).map_err(|err| err.to_string()),
+        };
+
+        if rest.is_empty() {
+            Ok(value)
+        } else {
+            Err(format!("{} trailing bytes", rest.len()))
+        }
+    }
+}
+
+/// Reads a chain of decoder names, like `base64 gzip msgpack` or `zstd > json`. The
+/// serialization format, if any, comes last.
+pub fn parse_chain(input: &str) -> Option<Vec<Decoder>> {
+    let chain = input.split(|c: char| c.is_whitespace() || matches!(c, ',' | '>' | '→'))
+        .filter(|name| !name.is_empty())
+        .map(|name| Decoder::ALL.into_iter().find(|decoder| decoder.name().eq_ignore_ascii_case(name)))
+        .collect::<Option<Vec<_>>>()?;
+
+    let formats_last = chain.iter().rev().skip(1).all(|decoder| !decoder.is_format());
+    (!chain.is_empty() && formats_last).then_some(chain)
+}
+
+/// `base64 → gzip → msgpack` style description of a chain.
+pub fn chain_name(chain: &[Decoder]) -> String {
+    chain.iter().map(Decoder::name).collect::<Vec<_>>().join(" → ")
+}
+
+/// A string as shown in the value view, and the decoders that got it there.
+pub struct Decoded {
+    pub chain: Vec<Decoder>,
+    pub text: String,
+    /// The decoded object or array, shown as a tree.
+    pub document: Option<Value>,
+}
+
+impl Decoded {
+    fn document(chain: Vec<Decoder>, value: Value) -> Decoded {
+        let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+        let document = (value.is_object() || value.is_array()).then_some(value);
+        Decoded { chain, text, document }
+    }
+
+    /// `bytes` as text, pretty-printed when it holds JSON.
+    pub fn bytes(chain: Vec<Decoder>, bytes: &[u8]) -> Decoded {
+        match serde_json::from_slice(bytes) {
+            Ok(value) => Decoded::document(chain, value),
+            Err(_) => Decoded { chain, text: escape_lines(bytes), document: None },
+        }
+    }
+}
+
+/// Runs `raw` through `chain`, failing with the step that could not decode it.
+pub fn decode(raw: &[u8], chain: &[Decoder]) -> Result<Decoded, String> {
+    let mut bytes = raw.to_vec();
+
+    for decoder in chain {
+        let failed = |err: String| format!("{} failed: {}", decoder.name(), err);
+        if decoder.is_format() {
+            let value = decoder.read(&bytes).map_err(failed)?;
+            return Ok(Decoded::document(chain.to_vec(), value));
+        }
+        bytes = decoder.unwrap(&bytes).map_err(failed)?;
+    }
+
+    Ok(Decoded::bytes(chain.to_vec(), &bytes))
+}
+
+/// Decodes `raw` with the decoders it looks encoded with, shown as stored when there are none.
+pub fn detect(raw: &[u8]) -> Decoded {
+    recognise(raw, 0).unwrap_or_else(|| Decoded::bytes(Vec::new(), raw))
+}
+
+/// Decodes `bytes` if it starts with a compression magic number, or holds a document.
+/// Base64 is only taken as such when what it decodes to is recognised too, plain words would
+/// pass for it otherwise.
+fn recognise(bytes: &[u8], depth: usize) -> Option<Decoded> {
+    if let Some((format, value)) = read_document(bytes) {
+        return Some(Decoded::document(vec![format], value));
+    }
+    if depth == MAX_DEPTH {
+        return None;
+    }
+
+    let (decoder, inner) = match compression_of(bytes) {
+        Some(decoder) => (decoder, decoder.unwrap(bytes).ok()?),
+        None if looks_like_base64(bytes) => (Decoder::Base64, decode_base64(bytes).ok()?),
+        None => return None,
+    };
+    let mut decoded = match recognise(&inner, depth + 1) {
+        Some(decoded) => decoded,
+        None if decoder != Decoder::Base64 => Decoded::bytes(Vec::new(), &inner),
+        None => return None,
+    };
+    decoded.chain.insert(0, decoder);
+    Some(decoded)
+}
+
+/// A JSON object or array in `bytes`, or a MessagePack or CBOR one in binary bytes. Those
+/// formats have no magic number, nearly any bytes start a valid document, so only maps and
+/// arrays read from all of `bytes` count.
+fn read_document(bytes: &[u8]) -> Option<(Decoder, Value)> {
+    let formats: &[Decoder] = if is_binary(bytes) {
+        &[Decoder::MsgPack, Decoder::Cbor]
+    } else {
+        &[Decoder::Json]
+    };
+
+    formats.iter()
+        .find_map(|format| format.read(bytes).ok()
+            .filter(|value| value.is_object() || value.is_array())
+            .map(|value| (*format, value)))
+}
+
+fn compression_of(bytes: &[u8]) -> Option<Decoder> {
+    match bytes {
+        [0x1f, 0x8b, ..] => Some(Decoder::Gzip),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Decoder::Zstd),
+        [0x04, 0x22, 0x4d, 0x18, ..] => Some(Decoder::Lz4),
+        // Deflate with a 32K window, and a header checksum
+        [0x78, flags, ..] if (0x7800 | *flags as u16).is_multiple_of(31) => Some(Decoder::Zlib),
+        _ => None,
+    }
+}
+
+fn looks_like_base64(bytes: &[u8]) -> bool {
+    let data = bytes.trim_ascii_end().strip_suffix(b"==")
+        .or_else(|| bytes.trim_ascii_end().strip_suffix(b"="))
+        .unwrap_or(bytes.trim_ascii_end());
+
+    data.len() >= 4 && data.iter().all(|byte| byte.is_ascii_alphanumeric() || b"+/-_".contains(byte))
+}
+
+/// Standard or URL-safe base64, padded or not.
+fn decode_base64(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let bytes = bytes.trim_ascii();
+    let engine = if bytes.iter().any(|byte| b"-_".contains(byte)) { BASE64_URL } else { BASE64 };
+
+    engine.decode(bytes).map_err(|err| err.to_string())
+}
+
+fn msgpack_to_json(value: rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(b) => Value::Bool(b),
+        rmpv::Value::Integer(int) => match (int.as_u64(), int.as_i64()) {
+            (Some(n), _) => n.into(),
+            (_, Some(n)) => n.into(),
+            _ => Value::Null,
+        },
+        rmpv::Value::F32(n) => f64::from(n).into(),
+        rmpv::Value::F64(n) => n.into(),
+        rmpv::Value::String(s) => Value::String(s.as_str().map(str::to_string).unwrap_or_else(|| escape(s.as_bytes()))),
+        rmpv::Value::Binary(bytes) => Value::String(escape(&bytes)),
+        rmpv::Value::Array(values) => Value::Array(values.into_iter().map(msgpack_to_json).collect()),
+        rmpv::Value::Map(entries) => Value::Object(entries.into_iter()
+            .map(|(key, value)| (key_name(msgpack_to_json(key)), msgpack_to_json(value)))
+            .collect()),
+        rmpv::Value::Ext(kind, data) => {
+            let mut ext = Map::new();
+            ext.insert("ext".to_string(), kind.into());
+            ext.insert("data".to_string(), Value::String(escape(&data)));
+            Value::Object(ext)
+        }
+    }
+}
+
+fn cbor_to_json(value: ciborium::Value) -> Value {
+    match value {
+        ciborium::Value::Null => Value::Null,
+        ciborium::Value::Bool(b) => Value::Bool(b),
+        ciborium::Value::Integer(int) => {
+            let n = i128::from(int);
+            match (u64::try_from(n), i64::try_from(n)) {
+                (Ok(n), _) => n.into(),
+                (_, Ok(n)) => n.into(),
+                _ => Value::String(n.to_string()),
+            }
+        }
+        ciborium::Value::Float(n) => n.into(),
+        ciborium::Value::Text(s) => Value::String(s),
+        ciborium::Value::Bytes(bytes) => Value::String(escape(&bytes)),
+        // Tags like dates annotate the value they wrap
+        ciborium::Value::Tag(_, value) => cbor_to_json(*value),
+        ciborium::Value::Array(values) => Value::Array(values.into_iter().map(cbor_to_json).collect()),
+        ciborium::Value::Map(entries) => Value::Object(entries.into_iter()
+            .map(|(key, value)| (key_name(cbor_to_json(key)), cbor_to_json(value)))
+            .collect()),
+        _ => Value::Null,
+    }
+}
+
+/// A JSON object key for a map key of any type.
+fn key_name(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        key => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn parses_decoder_chains() {
+        assert_eq!(parse_chain("base64 gzip msgpack"), Some(vec![Decoder::Base64, Decoder::Gzip, Decoder::MsgPack]));
+        assert_eq!(parse_chain("ZSTD > json"), Some(vec![Decoder::Zstd, Decoder::Json]));
+        assert_eq!(parse_chain("json gzip"), None);
+        assert_eq!(parse_chain("rot13"), None);
+        assert_eq!(parse_chain(""), None);
+    }
+
+    #[test]
+    fn detects_stacked_encodings() {
+        let plain = detect(b"hello world");
+        assert!(plain.chain.is_empty());
+        assert_eq!(plain.text, "hello world");
+
+        let json = BASE64.encode(gzip(br#"{"a":1}"#));
+        let decoded = detect(json.as_bytes());
+        assert_eq!(decoded.chain, vec![Decoder::Base64, Decoder::Gzip, Decoder::Json]);
+        assert_eq!(decoded.text, "{\n  \"a\": 1\n}");
+
+        // {"a": [1, b"\xff"]}
+        let decoded = detect(b"\x81\xa1a\x92\x01\xc4\x01\xff");
+        assert_eq!(decoded.chain, vec![Decoder::MsgPack]);
+        assert_eq!(decoded.text, "{\n  \"a\": [\n    1,\n    \"\\\\xff\"\n  ]\n}");
+
+        let zstd = [0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x58, 0x39, 0x00, 0x00, 0x7b, 0x22, 0x61, 0x22, 0x3a, 0x31, 0x7d];
+        assert_eq!(detect(&zstd).chain, vec![Decoder::Zstd, Decoder::Json]);
+
+        let lz4 = lz4_flex::compress_prepend_size(b"compressed text");
+        let decoded = decode(&lz4, &[Decoder::Lz4]).unwrap();
+        assert_eq!(decoded.text, "compressed text");
+    }
+
+    #[test]
+    fn reports_the_failing_decoder() {
+        let err = decode(b"not compressed", &[Decoder::Gzip, Decoder::Json]).err().unwrap();
+        assert!(err.starts_with("gzip failed"), "{}", err);
+
+        // Text read as an LZ4 block whose length prefix claims 4 GiB
+        let err = decode(b"\xff\xff\xff\xffnot lz4", &[Decoder::Lz4]).err().unwrap();
+        assert!(err.contains("too large"), "{}", err);
+
+        // Inflated past the cap rather than cut short at it
+        let bomb = gzip(&vec![0; MAX_DECODED as usize + 1]);
+        let err = decode(&bomb, &[Decoder::Gzip]).err().unwrap();
+        assert!(err.contains("too large"), "{}", err);
+    }
+}
@@ -0,0 +1,45 @@
+use redis::RedisError;
+use thiserror::Error;
+
+use crate::{database::DBError, value::escape};
+
+/// Errors raised while the TUI is running. They are shown in a popup instead of ending the app.
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("Could not scan keys: {0}")]
+    Scan(#[source] RedisError),
+    #[error("Could not read '{}': {source}", escape(.key))]
+    ReadKey {
+        key: Vec<u8>,
+        #[source]
+        source: RedisError,
+    },
+    #[error("Key '{}' no longer exists", escape(.0))]
+    MissingKey(Vec<u8>),
+    #[error("'{index}' is not an index of a list of {len} elements")]
+    InvalidIndex { index: String, len: usize },
+    #[error("'{0}' is not a range, expected min and max bounds")]
+    InvalidRange(String),
+    #[error("'{0}' is not a decoder chain, expected names like base64 gzip msgpack")]
+    InvalidDecoders(String),
+    #[error("Could not switch to db {db}: {source}")]
+    SelectDb {
+        db: i64,
+        #[source]
+        source: RedisError,
+    },
+    #[error("Could not read the keyspace: {0}")]
+    Keyspace(#[source] RedisError),
+    #[error("'{pattern}' is not a valid regex: {source}")]
+    InvalidSearch {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("Nothing loaded matches '{0}'")]
+    NoMatch(String),
+    #[error("Could not copy to the clipboard: {0}")]
+    Clipboard(#[source] std::io::Error),
+    #[error("Could not save config: {0}")]
+    Config(#[from] DBError),
+}
@@ -5,11 +5,13 @@ use std::{
 };
 use crossterm::event::{self, Event as CEvent, KeyEvent};
 
-use crate::app::App;
+use crate::{app::App, worker::WorkerEvent};
 
 pub enum Event<I> {
     Input(I),
     Tick,
+    /// A Redis worker finished a request.
+    Redis(Box<WorkerEvent>),
 }
 
 pub fn start_input_thread(tx: Sender<Event<KeyEvent>>, tick_rate: Duration) {
@@ -26,10 +28,8 @@ pub fn start_input_thread(tx: Sender<Event<KeyEvent>>, tick_rate: Duration) {
                 }
             }
 
-            if last_tick.elapsed() >= tick_rate {
-                if let Ok(_) = tx.send(Event::Tick) {
-                    last_tick = Instant::now();
-                }
+            if last_tick.elapsed() >= tick_rate && tx.send(Event::Tick).is_ok() {
+                last_tick = Instant::now();
             }
         }
     });
@@ -39,7 +39,8 @@ pub fn handle_input(app: &mut App, rx: &Receiver<Event<KeyEvent>>) {
         match rx.recv() {
             Ok(rec) => match rec {
                 Event::Input(event) => app.handle_input(event),
-                Event::Tick => {}
+                Event::Tick => app.on_tick(),
+                Event::Redis(event) => app.handle_redis_event(*event),
             },
             Err(e) => {
                 println!("rx.recv() failed: {}", e);
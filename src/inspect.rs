@@ -0,0 +1,171 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use redis::{self, ConnectionLike, RedisResult};
+
+use crate::app::{human_delta, human_size};
+
+/// Keys using more memory than this are not DUMPed to measure their serialized length.
+const MAX_DUMP: u64 = 16 * 1024 * 1024;
+
+/// What Redis knows about a key besides its value, as shown by the key inspector.
+pub struct KeyInfo {
+    pub key: Vec<u8>,
+    pub value_type: String,
+    pub encoding: Option<String>,
+    /// Bytes used by the key and its value, `None` where MEMORY USAGE is disabled.
+    pub memory: Option<u64>,
+    /// Milliseconds left to live, `None` for a persistent key.
+    pub pttl: Option<u64>,
+    /// Milliseconds since the epoch the key expires at.
+    pub expires_at: Option<u64>,
+    /// Seconds since the key was last read or written, only tracked by the LRU eviction policies.
+    pub idle: Option<u64>,
+    /// Logarithmic access counter, only tracked by the LFU eviction policies.
+    pub freq: Option<u64>,
+    /// Elements of a collection or bytes of a string, with their unit.
+    pub length: Option<(u64, &'static str)>,
+    /// Bytes of the DUMP of the key, `None` for too big ones, those of unknown size, or where DUMP
+    /// is denied.
+    pub serialized: Option<u64>,
+}
+
+impl KeyInfo {
+    pub fn memory_as_human(&self) -> String {
+        match self.memory {
+            Some(memory) => format!("{} ({} bytes)", human_size(memory), memory),
+            None => "-".to_string(),
+        }
+    }
+
+    /// `2h (7200000 ms), at 2024-05-01 12:00:00 UTC` style expiry.
+    pub fn expiry_as_human(&self) -> String {
+        match (self.pttl, self.expires_at) {
+            (Some(pttl), Some(at)) => format!("{} ({} ms), at {}", human_delta(pttl / 1000), pttl, utc_timestamp(at)),
+            (Some(pttl), None) => format!("{} ({} ms)", human_delta(pttl / 1000), pttl),
+            _ => "never".to_string(),
+        }
+    }
+
+    pub fn idle_as_human_delta(&self) -> String {
+        match self.idle {
+            Some(idle) => human_delta(idle),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn length_as_human(&self) -> String {
+        match self.length {
+            Some((length, unit)) => format!("{} {}", length, unit),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn serialized_as_human(&self) -> String {
+        match self.serialized {
+            Some(serialized) => format!("{} ({} bytes)", human_size(serialized), serialized),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// Reads the metadata of `key`, `None` when it does not exist. The OBJECT, MEMORY and DUMP
+/// commands may be disabled or not apply to the eviction policy, those fields are left out.
+pub fn read_key_info<C: ConnectionLike>(con: &mut C, key: &[u8]) -> RedisResult<Option<KeyInfo>> {
+    let value_type: String = redis::cmd("TYPE").arg(key).query(con)?;
+    if value_type == "none" {
+        return Ok(None);
+    }
+
+    let encoding = redis::cmd("OBJECT").arg("ENCODING").arg(key).query(con).ok();
+    let memory = redis::cmd("MEMORY").arg("USAGE").arg(key).query::<Option<u64>>(con).ok().flatten();
+    let idle = redis::cmd("OBJECT").arg("IDLETIME").arg(key).query(con).ok();
+    let freq = redis::cmd("OBJECT").arg("FREQ").arg(key).query(con).ok();
+
+    // -1 is a persistent key, -2 one that expired meanwhile
+    let pttl: i64 = redis::cmd("PTTL").arg(key).query(con)?;
+    let pttl = u64::try_from(pttl).ok();
+    let expires_at = match pttl {
+        // PEXPIRETIME needs Redis 7, otherwise the expiry is reckoned with the local clock
+        Some(pttl) => redis::cmd("PEXPIRETIME").arg(key).query::<i64>(con).ok()
+            .and_then(|at| u64::try_from(at).ok())
+            .or_else(|| {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+                Some(now.as_millis() as u64 + pttl)
+            }),
+        None => None,
+    };
+
+    let length = match length_command(&value_type) {
+        Some((command, unit)) => Some((redis::cmd(command).arg(key).query(con)?, unit)),
+        None => None,
+    };
+
+    // DUMP serializes the whole value while blocking the server, keys of unknown size may be huge
+    let serialized = match memory {
+        Some(memory) if memory <= MAX_DUMP => redis::cmd("DUMP").arg(key)
+            .query::<Option<Vec<u8>>>(con)
+            .ok()
+            .flatten()
+            .map(|dump| dump.len() as u64),
+        _ => None,
+    };
+
+    Ok(Some(KeyInfo {
+        key: key.to_vec(),
+        value_type,
+        encoding,
+        memory,
+        pttl,
+        expires_at,
+        idle,
+        freq,
+        length,
+        serialized,
+    }))
+}
+
+/// The command counting the elements of a key of `value_type`, and what they are.
+fn length_command(value_type: &str) -> Option<(&'static str, &'static str)> {
+    match value_type {
+        "string" => Some(("STRLEN", "bytes")),
+        "hash" => Some(("HLEN", "fields")),
+        "list" => Some(("LLEN", "elements")),
+        "set" => Some(("SCARD", "members")),
+        "zset" => Some(("ZCARD", "members")),
+        "stream" => Some(("XLEN", "entries")),
+        _ => None,
+    }
+}
+
+/// `2024-05-01 12:00:00 UTC` form of a time in milliseconds since the epoch.
+fn utc_timestamp(millis: u64) -> String {
+    let seconds = millis / 1000;
+    let (days, time) = (seconds / 86400, seconds % 86400);
+
+    // Days to a civil date, from Howard Hinnant's `civil_from_days`
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, time / 3600, time % 3600 / 60, time % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(utc_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(utc_timestamp(951_782_400_000), "2000-02-29 00:00:00 UTC");
+        assert_eq!(utc_timestamp(1_700_000_000_123), "2023-11-14 22:13:20 UTC");
+    }
+}
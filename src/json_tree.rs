@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+use serde_json::Value;
+
+/// A row of the JSON tree view: a node whose parents are all expanded.
+pub struct TreeNode<'a> {
+    /// JSONPath of the node, like `$.users[0].name`, it also names the node in the collapsed set.
+    pub path: String,
+    pub depth: usize,
+    /// Object key or array index of the node, `$` for the root.
+    pub label: String,
+    pub value: &'a Value,
+    /// Whether the children of an object or array are hidden.
+    pub collapsed: bool,
+}
+
+impl TreeNode<'_> {
+    /// The key and, for a scalar, the value of the node, as searched.
+    pub fn text(&self) -> String {
+        match self.value {
+            Value::Object(_) | Value::Array(_) => self.label.clone(),
+            leaf => format!("{}: {}", self.label, leaf),
+        }
+    }
+}
+
+/// The nodes of `root` to show, depth first, skipping the children of the `collapsed` paths.
+pub fn visible_nodes<'a>(root: &'a Value, collapsed: &HashSet<String>) -> Vec<TreeNode<'a>> {
+    let mut nodes = Vec::new();
+    push_node(&mut nodes, "$".to_string(), "$".to_string(), 0, root, collapsed);
+    nodes
+}
+
+fn push_node<'a>(
+    nodes: &mut Vec<TreeNode<'a>>,
+    path: String,
+    label: String,
+    depth: usize,
+    value: &'a Value,
+    collapsed: &HashSet<String>,
+) {
+    let is_collapsed = collapsed.contains(&path);
+    nodes.push(TreeNode { path: path.clone(), depth, label, value, collapsed: is_collapsed });
+    if is_collapsed {
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                push_node(nodes, child_path(&path, key), key.clone(), depth + 1, child, collapsed);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                push_node(nodes, format!("{}[{}]", path, i), i.to_string(), depth + 1, child, collapsed);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `parent.key`, or `parent["key"]` for keys that are not identifiers.
+fn child_path(parent: &str, key: &str) -> String {
+    let identifier = key.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    if identifier {
+        format!("{}.{}", parent, key)
+    } else {
+        format!("{}[{}]", parent, Value::String(key.to_string()))
+    }
+}
+
+/// `3 keys` style size of an object or array.
+pub fn child_count(value: &Value) -> Option<String> {
+    let (count, unit) = match value {
+        Value::Object(map) => (map.len(), "key"),
+        Value::Array(items) => (items.len(), "item"),
+        _ => return None,
+    };
+    Some(format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_expanded_nodes_with_paths() {
+        let doc = serde_json::json!({ "users": [{ "name": "a" }], "odd key": 1 });
+
+        let paths: Vec<String> = visible_nodes(&doc, &HashSet::new()).into_iter().map(|node| node.path).collect();
+        assert_eq!(paths, ["$", "$[\"odd key\"]", "$.users", "$.users[0]", "$.users[0].name"]);
+
+        let collapsed = HashSet::from(["$.users".to_string()]);
+        let nodes = visible_nodes(&doc, &collapsed);
+        assert_eq!(nodes.len(), 3);
+        assert!(nodes[2].collapsed);
+        assert_eq!(child_count(nodes[2].value).as_deref(), Some("1 item"));
+    }
+}
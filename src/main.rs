@@ -1,4 +1,5 @@
 use std::{
+    panic,
     process::exit,
     io::{self, Stdout},
     time::{Duration},
@@ -9,7 +10,8 @@ use tui::{
     Terminal,
 };
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent},
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture, KeyEvent},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -17,25 +19,42 @@ use crossterm::{
 mod database;
 mod ui;
 mod app;
+mod connection;
+mod decode;
+mod error;
 mod input;
-
-fn setup_terminal() -> Result<(), ()> {
-    enable_raw_mode().expect("can run in raw mode");
-    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
-        .expect("can run in alternate screen with mouse capture");
+mod inspect;
+mod json_tree;
+mod rules;
+mod server_manager;
+mod stream;
+mod value;
+mod worker;
+
+fn setup_terminal() -> crossterm::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
     Ok(())
 }
 
-fn cleanup_terminal() -> Result<(), ()> {
-    disable_raw_mode().expect("can disable raw mode");
-    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
-        .expect("can leave alternate screen and disable mouse capture");
+fn cleanup_terminal() -> crossterm::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)?;
     Ok(())
 }
 
+/// Restores the terminal before a panic message is printed, so a bug does not leave it in raw mode.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = cleanup_terminal();
+        default_hook(info);
+    }));
+}
+
 fn create_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>, std::io::Error> {
     let backend = CrosstermBackend::new(io::stdout());
-    return Terminal::new(backend);
+    Terminal::new(backend)
 }
 
 
@@ -44,125 +63,49 @@ fn draw_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut ap
     Ok(())
 }
 
-fn main_redis() {
-    let mut app = app::App::new(
-        vec![
-            // app::RedisServer::new(
-            //     "SMB Pro".to_string(),
-            //     "smb-redis-pro-001.rzt4nj.0001.eun1.cache.amazonaws.com".to_string(),
-            //     6379,
-            //     None,
-            //     None,
-            // ),
-            // app::RedisServer::new(
-            //     "SMB Pre".to_string(),
-            //     "smb-redis-pre.rzt4nj.ng.0001.eun1.cache.amazonaws.com".to_string(),
-            //     6379,
-            //     None,
-            //     None,
-            // ),
-            app::RedisServer::new(
-                "SMB Dev".to_string(),
-                "smb-redis-dev.wrh1jp.ng.0001.euw1.cache.amazonaws.com".to_string(),
-                6379,
-                None,
-                None,
-            )
-        ],
-        None
-    );
-
-    app.get_current_server_mut()
-        .connect()
-        .expect("can connect to server");
-
-    app.get_current_server_mut()
-        .get_session_mut()
-        .expect("can get connection")
-        .scan("*".to_string())
-        .expect("can scan");
-
-    let iter = app.get_current_server()
-        .get_session()
-        .expect("can get connection")
-        .iter_keys();
-
-    for (key, meta) in iter {
-        println!("{:?} -> {:?}", key, meta);
-    }
-}
-
 fn main_rudis() -> Result<(), Box<dyn std::error::Error>> {
+    let db_path = database::DB::default_path()?;
+    let db = database::DB::load_or_create(&db_path)?;
+    let servers = db.server_configs
+        .into_iter()
+        .map(app::RedisServer::from)
+        .collect::<Vec<app::RedisServer>>();
+    let display_rules = db.display_rules
+        .iter()
+        .map(|rule| rule.parse())
+        .collect::<Result<Vec<rules::DisplayRule>, _>>()?;
+
     let (tx, rx) = mpsc::channel::<input::Event<KeyEvent>>();
     let tick_rate = Duration::from_millis(200);
 
-    input::start_input_thread(tx, tick_rate);
+    input::start_input_thread(tx.clone(), tick_rate);
 
-    setup_terminal().or_else(|_| -> Result<(), io::Error> {
-        cleanup_terminal().expect("can cleanup terminal");
-        exit(1);
+    install_panic_hook();
+    setup_terminal().inspect_err(|_| {
+        let _ = cleanup_terminal();
     })?;
-    let mut terminal = create_terminal().or_else(|e| {
-        cleanup_terminal().expect("can cleanup terminal");
-        Err(e)
+    let mut terminal = create_terminal().inspect_err(|_| {
+        let _ = cleanup_terminal();
     })?;
 
-    let mut app = app::App::new(
-        vec![
-            app::RedisServer::new(
-                "SMB Pro".to_string(),
-                "smb-redis-pro-001.rzt4nj.0001.eun1.cache.amazonaws.com".to_string(),
-                6379,
-                None,
-                None,
-            ),
-            app::RedisServer::new(
-                "SMB Pre".to_string(),
-                "smb-redis-pre.rzt4nj.ng.0001.eun1.cache.amazonaws.com".to_string(),
-                6379,
-                None,
-                None,
-            ),
-            app::RedisServer::new(
-                "SMB Dev".to_string(),
-                "smb-redis-dev.wrh1jp.ng.0001.euw1.cache.amazonaws.com".to_string(),
-                6379,
-                None,
-                None,
-            )
-        ],
-        None
-    );
-
+    let mut app = app::App::new(servers, display_rules, db_path, None, tx);
 
     while app.running {
-        draw_terminal(&mut terminal, &mut app).or_else(|e| {
-            cleanup_terminal().expect("can cleanup terminal");
-            Err(e)
+        draw_terminal(&mut terminal, &mut app).inspect_err(|_| {
+            let _ = cleanup_terminal();
         })?;
 
         input::handle_input(&mut app, &rx);
     }
 
-    cleanup_terminal().expect("can cleanup terminal");
-    terminal.show_cursor()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)
-        .expect("can leave alternate screen and disable mouse capture");
+    cleanup_terminal()?;
 
     Ok(())
 }
 
-fn main_db() {
-    let db = database::DB::DB_V1_0(database::DB_V1_0 {
-        version: database::DBVersions::V1_0,
-        server_configs: Vec::new(),
-    });
-
-    println!("{:?}", db);
-}
-
 fn main() {
-    main_rudis().expect("can run rudis");
-    // main_db();
-    // main_redis();
+    if let Err(e) = main_rudis() {
+        eprintln!("rudis: {}", e);
+        exit(1);
+    }
 }
@@ -0,0 +1,164 @@
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+use crate::decode::{self, Decoder};
+
+#[derive(Error, Debug)]
+#[error("invalid display rule '{0}', expected one like `session:* => msgpack`, `cache:* => gzip,text` or `metrics:* => hex`")]
+pub struct InvalidRule(pub String);
+
+/// A `pattern => display` rule from the config, picking how the values of the keys matching a
+/// glob-style pattern are shown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayRule {
+    pub pattern: String,
+    pub display: Display,
+    /// The rule as written in the config, saved back unchanged.
+    source: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Display {
+    /// Run strings through these decoders, none shows them as stored without detecting any.
+    Decoders(Vec<Decoder>),
+    /// Show strings as a hex dump of their bytes.
+    Hex,
+}
+
+impl DisplayRule {
+    /// The first of `rules` matching `key`.
+    pub fn find<'a>(rules: &'a [DisplayRule], key: &[u8]) -> Option<&'a DisplayRule> {
+        rules.iter().find(|rule| glob_match(rule.pattern.as_bytes(), key))
+    }
+}
+
+impl FromStr for DisplayRule {
+    type Err = InvalidRule;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidRule(rule.to_string());
+        let (pattern, display) = rule.split_once("=>").ok_or_else(invalid)?;
+        let (pattern, display) = (pattern.trim(), display.trim());
+        if pattern.is_empty() {
+            return Err(invalid());
+        }
+
+        let display = match display {
+            "hex" => Display::Hex,
+            "text" => Display::Decoders(Vec::new()),
+            chain => {
+                // A trailing `text` only says the decoded bytes are shown as they are
+                let chain = match chain.strip_suffix("text") {
+                    Some(rest) if rest.ends_with([',', ' ', '>']) => rest,
+                    _ => chain,
+                };
+                Display::Decoders(decode::parse_chain(chain).ok_or_else(invalid)?)
+            }
+        };
+        Ok(DisplayRule { pattern: pattern.to_string(), display, source: rule.to_string() })
+    }
+}
+
+impl fmt::Display for DisplayRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Whether `key` matches the glob-style `pattern` the way KEYS and SCAN MATCH do: `*`, `?`,
+/// `[a-z]` and `[^abc]` classes, and `\` escapes.
+pub fn glob_match(pattern: &[u8], key: &[u8]) -> bool {
+    match pattern {
+        [] => key.is_empty(),
+        [b'*', ..] => {
+            let rest = pattern.iter().position(|&c| c != b'*').map_or(&[][..], |i| &pattern[i..]);
+            (0..=key.len()).any(|i| glob_match(rest, &key[i..]))
+        }
+        [b'?', rest @ ..] => !key.is_empty() && glob_match(rest, &key[1..]),
+        [b'[', class @ ..] => {
+            let Some((&c, key_rest)) = key.split_first() else { return false };
+            let (matched, rest) = match_class(class, c);
+            matched && glob_match(rest, key_rest)
+        }
+        [b'\\', escaped, rest @ ..] | [escaped, rest @ ..] => {
+            key.first() == Some(escaped) && glob_match(rest, &key[1..])
+        }
+    }
+}
+
+/// Whether `c` is in the class `class` starts, after its `[`, and the pattern following it.
+fn match_class(mut class: &[u8], c: u8) -> (bool, &[u8]) {
+    let negate = class.first() == Some(&b'^');
+    if negate {
+        class = &class[1..];
+    }
+
+    let mut matched = false;
+    loop {
+        match class {
+            // An unterminated class ends with the pattern
+            [] => break,
+            [b']', rest @ ..] => {
+                class = rest;
+                break;
+            }
+            [b'\\', escaped, rest @ ..] => {
+                matched |= *escaped == c;
+                class = rest;
+            }
+            [start, b'-', end, rest @ ..] if *end != b']' => {
+                let (low, high) = if start <= end { (*start, *end) } else { (*end, *start) };
+                matched |= (low..=high).contains(&c);
+                class = rest;
+            }
+            [member, rest @ ..] => {
+                matched |= *member == c;
+                class = rest;
+            }
+        }
+    }
+
+    (matched != negate, class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_redis_glob_patterns() {
+        assert!(glob_match(b"session:*", b"session:42"));
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"a*b*c", b"a--b--c"));
+        assert!(!glob_match(b"a*b*c", b"a--b--"));
+        assert!(glob_match(b"user:?", b"user:7"));
+        assert!(!glob_match(b"user:?", b"user:"));
+        assert!(glob_match(b"h[ae]llo", b"hello"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"v[0-9]", b"v5"));
+        assert!(glob_match(b"a\\*", b"a*"));
+        assert!(!glob_match(b"a\\*", b"ab"));
+    }
+
+    #[test]
+    fn parses_display_rules() {
+        let rule: DisplayRule = "session:* => msgpack".parse().unwrap();
+        assert_eq!(rule.pattern, "session:*");
+        assert_eq!(rule.display, Display::Decoders(vec![Decoder::MsgPack]));
+
+        let rule: DisplayRule = "cache:html:* => gzip,text".parse().unwrap();
+        assert_eq!(rule.display, Display::Decoders(vec![Decoder::Gzip]));
+        assert_eq!(rule.to_string(), "cache:html:* => gzip,text");
+
+        let rule: DisplayRule = "blobs:* => zstd > json".parse().unwrap();
+        assert_eq!(rule.display, Display::Decoders(vec![Decoder::Zstd, Decoder::Json]));
+        assert_eq!(rule.to_string(), "blobs:* => zstd > json");
+
+        let rule: DisplayRule = "metrics:*=>hex".parse().unwrap();
+        assert_eq!(rule.display, Display::Hex);
+
+        assert!("session:*".parse::<DisplayRule>().is_err());
+        assert!(" => json".parse::<DisplayRule>().is_err());
+        assert!("a:* => rot13".parse::<DisplayRule>().is_err());
+    }
+}
@@ -0,0 +1,630 @@
+use std::{
+    path::PathBuf,
+    str::FromStr,
+};
+use crossterm::event::{KeyEvent, KeyCode, KeyModifiers};
+use tui::style::Color;
+
+use crate::{
+    app::RedisServer,
+    database::{RedisServerConf, AddressConf, SentinelAddrConf, AuthConf, TlsConf},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FormField {
+    Name,
+    Host,
+    Port,
+    Socket,
+    Sentinels,
+    MasterName,
+    ReadReplica,
+    Username,
+    Password,
+    Db,
+    Tls,
+    CaCert,
+    ClientCert,
+    ClientKey,
+    Insecure,
+    Color,
+    ReadOnly,
+    Cluster,
+}
+
+impl FormField {
+    pub const ALL: [FormField; 18] = [
+        FormField::Name,
+        FormField::Host,
+        FormField::Port,
+        FormField::Socket,
+        FormField::Sentinels,
+        FormField::MasterName,
+        FormField::ReadReplica,
+        FormField::Username,
+        FormField::Password,
+        FormField::Db,
+        FormField::Tls,
+        FormField::CaCert,
+        FormField::ClientCert,
+        FormField::ClientKey,
+        FormField::Insecure,
+        FormField::Color,
+        FormField::ReadOnly,
+        FormField::Cluster,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FormField::Name => "Name",
+            FormField::Host => "Host",
+            FormField::Port => "Port",
+            FormField::Socket => "Socket",
+            FormField::Sentinels => "Sentinels",
+            FormField::MasterName => "Master",
+            FormField::ReadReplica => "Replica",
+            FormField::Username => "Username",
+            FormField::Password => "Password",
+            FormField::Db => "DB",
+            FormField::Tls => "TLS",
+            FormField::CaCert => "CA cert",
+            FormField::ClientCert => "Client cert",
+            FormField::ClientKey => "Client key",
+            FormField::Insecure => "Insecure",
+            FormField::Color => "Color",
+            FormField::ReadOnly => "Read-only",
+            FormField::Cluster => "Cluster",
+        }
+    }
+
+    pub fn is_toggle(&self) -> bool {
+        matches!(self, FormField::ReadReplica | FormField::Tls | FormField::Insecure | FormField::ReadOnly | FormField::Cluster)
+    }
+}
+
+pub struct ServerForm {
+    /// Index of the server being edited, `None` when adding a new one.
+    pub editing: Option<usize>,
+    pub focused: usize,
+    pub name: String,
+    pub host: String,
+    pub port: String,
+    pub socket: String,
+    /// Comma separated `host:port` list, the port defaulting to 26379.
+    pub sentinels: String,
+    pub master_name: String,
+    pub read_replica: bool,
+    pub username: String,
+    pub password: String,
+    pub db: String,
+    pub tls: bool,
+    pub ca_cert: String,
+    pub client_cert: String,
+    pub client_key: String,
+    pub insecure: bool,
+    pub color: String,
+    pub read_only: bool,
+    pub cluster: bool,
+    /// Validation error or result of the last connection test.
+    pub status: Option<Result<String, String>>,
+    /// Id of the connection test running in the background.
+    pub testing: Option<u64>,
+}
+
+impl ServerForm {
+    pub fn new() -> ServerForm {
+        ServerForm {
+            editing: None,
+            focused: 0,
+            name: "".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: "6379".to_string(),
+            socket: "".to_string(),
+            sentinels: "".to_string(),
+            master_name: "".to_string(),
+            read_replica: false,
+            username: "".to_string(),
+            password: "".to_string(),
+            db: "0".to_string(),
+            tls: false,
+            ca_cert: "".to_string(),
+            client_cert: "".to_string(),
+            client_key: "".to_string(),
+            insecure: false,
+            color: "".to_string(),
+            read_only: false,
+            cluster: false,
+            status: None,
+            testing: None,
+        }
+    }
+
+    pub fn from_conf(conf: RedisServerConf, editing: Option<usize>) -> ServerForm {
+        let (username, password) = match conf.auth {
+            Some(auth) => (auth.username.unwrap_or_default(), auth.password),
+            None => ("".to_string(), "".to_string()),
+        };
+
+        let mut form = ServerForm::new();
+        match conf.address {
+            AddressConf::Tcp { host, port } => {
+                form.host = host;
+                form.port = port.to_string();
+            }
+            AddressConf::Unix { path } => {
+                form.host.clear();
+                form.port.clear();
+                form.socket = path.display().to_string();
+            }
+            AddressConf::Sentinel { master_name, sentinels, read_replica } => {
+                form.host.clear();
+                form.port.clear();
+                form.sentinels = sentinels
+                    .iter()
+                    .map(|sentinel| format!("{}:{}", sentinel.host, sentinel.port))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                form.master_name = master_name;
+                form.read_replica = read_replica;
+            }
+        }
+        let tls = conf.tls.clone().unwrap_or_default();
+        let path_string = |path: Option<PathBuf>| path
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+
+        ServerForm {
+            editing,
+            name: conf.name,
+            username,
+            password,
+            db: conf.db.to_string(),
+            tls: conf.tls.is_some(),
+            ca_cert: path_string(tls.ca_cert),
+            client_cert: path_string(tls.client_cert),
+            client_key: path_string(tls.client_key),
+            insecure: tls.insecure,
+            color: conf.color.unwrap_or_default(),
+            read_only: conf.read_only,
+            cluster: conf.cluster,
+            ..form
+        }
+    }
+
+    pub fn focused_field(&self) -> FormField {
+        FormField::ALL[self.focused]
+    }
+
+    pub fn value(&self, field: FormField) -> String {
+        match field {
+            FormField::Name => self.name.clone(),
+            FormField::Host => self.host.clone(),
+            FormField::Port => self.port.clone(),
+            FormField::Socket => self.socket.clone(),
+            FormField::Sentinels => self.sentinels.clone(),
+            FormField::MasterName => self.master_name.clone(),
+            FormField::ReadReplica => toggle_label(self.read_replica),
+            FormField::Username => self.username.clone(),
+            FormField::Password => "*".repeat(self.password.chars().count()),
+            FormField::Db => self.db.clone(),
+            FormField::Tls => toggle_label(self.tls),
+            FormField::CaCert => self.ca_cert.clone(),
+            FormField::ClientCert => self.client_cert.clone(),
+            FormField::ClientKey => self.client_key.clone(),
+            FormField::Insecure => toggle_label(self.insecure),
+            FormField::Color => self.color.clone(),
+            FormField::ReadOnly => toggle_label(self.read_only),
+            FormField::Cluster => toggle_label(self.cluster),
+        }
+    }
+
+    fn text_mut(&mut self, field: FormField) -> Option<&mut String> {
+        match field {
+            FormField::Name => Some(&mut self.name),
+            FormField::Host => Some(&mut self.host),
+            FormField::Port => Some(&mut self.port),
+            FormField::Socket => Some(&mut self.socket),
+            FormField::Sentinels => Some(&mut self.sentinels),
+            FormField::MasterName => Some(&mut self.master_name),
+            FormField::Username => Some(&mut self.username),
+            FormField::Password => Some(&mut self.password),
+            FormField::Db => Some(&mut self.db),
+            FormField::CaCert => Some(&mut self.ca_cert),
+            FormField::ClientCert => Some(&mut self.client_cert),
+            FormField::ClientKey => Some(&mut self.client_key),
+            FormField::Color => Some(&mut self.color),
+            FormField::ReadReplica | FormField::Tls | FormField::Insecure | FormField::ReadOnly | FormField::Cluster => None,
+        }
+    }
+
+    fn toggle(&mut self, field: FormField) {
+        match field {
+            FormField::ReadReplica => self.read_replica = !self.read_replica,
+            FormField::Tls => self.tls = !self.tls,
+            FormField::Insecure => self.insecure = !self.insecure,
+            FormField::ReadOnly => self.read_only = !self.read_only,
+            FormField::Cluster => self.cluster = !self.cluster,
+            _ => {}
+        }
+    }
+
+    /// Builds a config from the form, `taken_names` being the names of all other servers.
+    pub fn validate(&self, taken_names: &[&str]) -> Result<RedisServerConf, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Name is required".to_string());
+        }
+        if taken_names.contains(&name) {
+            return Err(format!("A server named '{}' already exists", name));
+        }
+
+        let socket = self.socket.trim();
+        let sentinels = self.sentinels.trim();
+        let address = if !sentinels.is_empty() {
+            if !socket.is_empty() {
+                return Err("Use either sentinels or a socket, not both".to_string());
+            }
+            if self.cluster {
+                return Err("Cluster mode is not supported with sentinels".to_string());
+            }
+            let certs = [&self.ca_cert, &self.client_cert, &self.client_key];
+            if self.tls && certs.iter().any(|path| !path.trim().is_empty()) {
+                return Err("Custom TLS certificates are not supported with sentinels".to_string());
+            }
+            let master_name = self.master_name.trim();
+            if master_name.is_empty() {
+                return Err("Sentinels need the master name to look up".to_string());
+            }
+
+            AddressConf::Sentinel {
+                master_name: master_name.to_string(),
+                sentinels: parse_sentinels(sentinels)?,
+                read_replica: self.read_replica,
+            }
+        } else if self.read_replica {
+            return Err("Replica reads are only supported with sentinels".to_string());
+        } else if !socket.is_empty() {
+            if self.tls {
+                return Err("TLS is not supported over a unix socket".to_string());
+            }
+            if self.cluster {
+                return Err("Cluster mode is not supported over a unix socket".to_string());
+            }
+            AddressConf::Unix { path: PathBuf::from(socket) }
+        } else {
+            let host = self.host.trim();
+            if host.is_empty() {
+                return Err("Host or socket is required".to_string());
+            }
+
+            let port = match self.port.trim().parse::<u16>() {
+                Ok(port) if port > 0 => port,
+                _ => return Err("Port must be a number between 1 and 65535".to_string()),
+            };
+
+            AddressConf::Tcp { host: host.to_string(), port }
+        };
+
+        let db = match self.db.trim().parse::<i64>() {
+            Ok(db) if db >= 0 => db,
+            _ => return Err("DB must be a positive number".to_string()),
+        };
+        if self.cluster && db != 0 {
+            return Err("Redis Cluster only supports DB 0".to_string());
+        }
+
+        let username = self.username.trim();
+        let auth = match (username.is_empty(), self.password.is_empty()) {
+            (true, true) => None,
+            (false, true) => return Err("A username requires a password".to_string()),
+            (true, false) => Some(AuthConf { username: None, password: self.password.clone() }),
+            (false, false) => Some(AuthConf {
+                username: Some(username.to_string()),
+                password: self.password.clone(),
+            }),
+        };
+
+        let tls = if self.tls {
+            let path = |value: &str| {
+                let value = value.trim();
+                (!value.is_empty()).then(|| PathBuf::from(value))
+            };
+            let tls = TlsConf {
+                ca_cert: path(&self.ca_cert),
+                client_cert: path(&self.client_cert),
+                client_key: path(&self.client_key),
+                insecure: self.insecure,
+            };
+            if tls.client_cert.is_some() != tls.client_key.is_some() {
+                return Err("A client cert needs a client key, and the other way around".to_string());
+            }
+            Some(tls)
+        } else {
+            None
+        };
+
+        let color = self.color.trim();
+        if !color.is_empty() && Color::from_str(color).is_err() {
+            return Err(format!("'{}' is not a color name, index or #rrggbb value", color));
+        }
+
+        Ok(RedisServerConf {
+            name: name.to_string(),
+            address,
+            auth,
+            db,
+            tls,
+            color: (!color.is_empty()).then(|| color.to_string()),
+            read_only: self.read_only,
+            cluster: self.cluster,
+        })
+    }
+}
+
+/// Parses a comma separated `host:port` list, the port defaulting to the sentinel port 26379.
+fn parse_sentinels(sentinels: &str) -> Result<Vec<SentinelAddrConf>, String> {
+    sentinels
+        .split(',')
+        .map(str::trim)
+        .filter(|sentinel| !sentinel.is_empty())
+        .map(|sentinel| {
+            let (host, port) = match sentinel.rsplit_once(':') {
+                Some((host, port)) => match port.parse::<u16>() {
+                    Ok(port) if port > 0 => (host, port),
+                    _ => return Err(format!("'{}' is not a valid sentinel port", port)),
+                },
+                None => (sentinel, 26379),
+            };
+            if host.is_empty() {
+                return Err(format!("'{}' is missing a sentinel host", sentinel));
+            }
+
+            Ok(SentinelAddrConf { host: host.to_string(), port })
+        })
+        .collect()
+}
+
+fn toggle_label(value: bool) -> String {
+    if value { "[x]" } else { "[ ]" }.to_string()
+}
+
+pub enum ServerManagerAction {
+    None,
+    Close,
+    Save(Option<usize>, Box<RedisServerConf>),
+    Delete(usize),
+    /// Test a connection to the config in the background, replying to the open form.
+    Test(Box<RedisServerConf>),
+}
+
+pub struct ServerManager {
+    pub selected: usize,
+    pub form: Option<ServerForm>,
+    pub confirm_delete: bool,
+    /// Message from the last applied action, e.g. a failure to write the config file.
+    pub status: Option<String>,
+}
+
+impl ServerManager {
+    pub fn new(selected: usize) -> ServerManager {
+        ServerManager {
+            selected,
+            form: None,
+            confirm_delete: false,
+            status: None,
+        }
+    }
+
+    pub fn handle_input(&mut self, input: KeyEvent, servers: &[RedisServer]) -> ServerManagerAction {
+        if self.form.is_some() {
+            return self.handle_form_input(input, servers);
+        }
+
+        let confirm_delete = self.confirm_delete;
+        self.confirm_delete = false;
+
+        match input.code {
+            KeyCode::Esc | KeyCode::Char('q') => return ServerManagerAction::Close,
+            KeyCode::Char('j') | KeyCode::Down if self.selected + 1 < servers.len() => {
+                self.selected += 1;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Char('a') => {
+                self.form = Some(ServerForm::new());
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(server) = servers.get(self.selected) {
+                    self.form = Some(ServerForm::from_conf(server.into(), Some(self.selected)));
+                }
+            }
+            KeyCode::Char('y') => {
+                if let Some(server) = servers.get(self.selected) {
+                    let mut form = ServerForm::from_conf(server.into(), None);
+                    form.name = format!("{} (copy)", form.name);
+                    self.form = Some(form);
+                }
+            }
+            KeyCode::Char('x') if self.selected < servers.len() => {
+                if confirm_delete {
+                    return ServerManagerAction::Delete(self.selected);
+                }
+                self.confirm_delete = true;
+            }
+            _ => {}
+        }
+
+        ServerManagerAction::None
+    }
+
+    fn handle_form_input(&mut self, input: KeyEvent, servers: &[RedisServer]) -> ServerManagerAction {
+        let form = self.form.as_mut().expect("form to be open");
+        let field = form.focused_field();
+
+        match input.code {
+            KeyCode::Esc => {
+                self.form = None;
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                form.focused = (form.focused + 1) % FormField::ALL.len();
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                form.focused = (form.focused + FormField::ALL.len() - 1) % FormField::ALL.len();
+            }
+            KeyCode::Char('t') if input.modifiers.contains(KeyModifiers::CONTROL) => {
+                let taken_names = other_names(servers, form.editing);
+                match form.validate(&taken_names) {
+                    Ok(conf) => {
+                        form.status = Some(Ok("Testing connection...".to_string()));
+                        return ServerManagerAction::Test(Box::new(conf));
+                    }
+                    Err(e) => form.status = Some(Err(e)),
+                }
+            }
+            KeyCode::Char(' ') if field.is_toggle() => {
+                form.toggle(field);
+            }
+            KeyCode::Char(c) => {
+                if let Some(value) = form.text_mut(field) {
+                    value.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(value) = form.text_mut(field) {
+                    value.pop();
+                }
+            }
+            KeyCode::Enter => {
+                let taken_names = other_names(servers, form.editing);
+                match form.validate(&taken_names) {
+                    Ok(conf) => {
+                        let editing = form.editing;
+                        self.form = None;
+                        return ServerManagerAction::Save(editing, Box::new(conf));
+                    }
+                    Err(e) => form.status = Some(Err(e)),
+                }
+            }
+            _ => {}
+        }
+
+        ServerManagerAction::None
+    }
+}
+
+fn other_names(servers: &[RedisServer], editing: Option<usize>) -> Vec<&str> {
+    servers.iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) != editing)
+        .map(|(_, server)| server.name.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(edit: impl FnOnce(&mut ServerForm)) -> ServerForm {
+        let mut form = ServerForm::new();
+        form.name = "local".to_string();
+        edit(&mut form);
+        form
+    }
+
+    fn rejects(edit: impl FnOnce(&mut ServerForm), message: &str) {
+        let err = form(edit).validate(&[]).expect_err("to be rejected");
+        assert!(err.contains(message), "{}", err);
+    }
+
+    #[test]
+    fn validates_names_and_addresses() {
+        let conf = form(|_| {}).validate(&["other"]).expect("to be valid");
+        assert_eq!(conf.address, AddressConf::Tcp { host: "127.0.0.1".to_string(), port: 6379 });
+        assert!(form(|_| {}).validate(&["local"]).is_err());
+
+        let conf = form(|form| form.socket = "/tmp/redis.sock".to_string()).validate(&[]).expect("to be valid");
+        assert_eq!(conf.address, AddressConf::Unix { path: PathBuf::from("/tmp/redis.sock") });
+        rejects(|form| {
+            form.socket = "/tmp/redis.sock".to_string();
+            form.tls = true;
+        }, "unix socket");
+
+        for port in ["0", "65536", "redis"] {
+            rejects(|form| form.port = port.to_string(), "Port");
+        }
+        let conf = form(|form| form.port = "65535".to_string()).validate(&[]).expect("to be valid");
+        assert_eq!(conf.address, AddressConf::Tcp { host: "127.0.0.1".to_string(), port: 65535 });
+    }
+
+    #[test]
+    fn validates_sentinels() {
+        let conf = form(|form| {
+            form.sentinels = "a:26380, b".to_string();
+            form.master_name = "mymaster".to_string();
+            form.tls = true;
+        }).validate(&[]).expect("to be valid");
+        assert_eq!(conf.address, AddressConf::Sentinel {
+            master_name: "mymaster".to_string(),
+            sentinels: vec![
+                SentinelAddrConf { host: "a".to_string(), port: 26380 },
+                SentinelAddrConf { host: "b".to_string(), port: 26379 },
+            ],
+            read_replica: false,
+        });
+
+        rejects(|form| form.sentinels = "a".to_string(), "master name");
+        rejects(|form| form.read_replica = true, "only supported with sentinels");
+        for cert in ["ca", "cert", "key"] {
+            rejects(|form| {
+                form.sentinels = "a".to_string();
+                form.master_name = "mymaster".to_string();
+                form.tls = true;
+                match cert {
+                    "ca" => form.ca_cert = "ca.pem".to_string(),
+                    "cert" => form.client_cert = "client.pem".to_string(),
+                    _ => form.client_key = "client.key".to_string(),
+                }
+            }, "not supported with sentinels");
+        }
+    }
+
+    #[test]
+    fn validates_tls_cluster_auth_and_db() {
+        let conf = form(|form| {
+            form.tls = true;
+            form.client_cert = "client.pem".to_string();
+            form.client_key = " client.key ".to_string();
+        }).validate(&[]).expect("to be valid");
+        assert_eq!(conf.tls, Some(TlsConf {
+            ca_cert: None,
+            client_cert: Some(PathBuf::from("client.pem")),
+            client_key: Some(PathBuf::from("client.key")),
+            insecure: false,
+        }));
+        rejects(|form| {
+            form.tls = true;
+            form.client_cert = "client.pem".to_string();
+        }, "client key");
+        rejects(|form| {
+            form.tls = true;
+            form.client_key = "client.key".to_string();
+        }, "client key");
+
+        assert!(form(|form| form.cluster = true).validate(&[]).expect("to be valid").cluster);
+        rejects(|form| {
+            form.cluster = true;
+            form.db = "1".to_string();
+        }, "DB 0");
+
+        let conf = form(|form| {
+            form.username = "app".to_string();
+            form.password = "secret".to_string();
+        }).validate(&[]).expect("to be valid");
+        assert_eq!(conf.auth, Some(AuthConf { username: Some("app".to_string()), password: "secret".to_string() }));
+        rejects(|form| form.username = "app".to_string(), "requires a password");
+
+        assert_eq!(form(|form| form.db = "3".to_string()).validate(&[]).expect("to be valid").db, 3);
+        rejects(|form| form.db = "-1".to_string(), "DB must be");
+    }
+}
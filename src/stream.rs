@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use redis::{self, ConnectionLike, FromRedisValue, RedisResult, Value};
+
+use crate::{app::human_delta, value::escape};
+
+/// Entries read per page.
+const PAGE: usize = 100;
+
+/// Pending entries listed for a consumer group.
+const PENDING_PAGE: usize = 100;
+
+pub struct StreamEntry {
+    pub id: String,
+    pub fields: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StreamEntry {
+    /// `field=value` pairs of the entry, escaped.
+    pub fn fields_as_text(&self) -> String {
+        self.fields.iter()
+            .map(|(field, value)| format!("{}={}", escape(field), escape(value)))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// The `XINFO STREAM` summary of a stream.
+pub struct StreamInfo {
+    pub length: usize,
+    pub groups: usize,
+    pub last_generated_id: String,
+    pub first_id: Option<String>,
+    pub last_id: Option<String>,
+}
+
+/// A stream's summary and the entries read from it so far.
+pub struct StreamValue {
+    pub info: StreamInfo,
+    pub entries: Vec<StreamEntry>,
+    /// Id to read the next page of entries from, `None` once they are all read.
+    pub next: Option<String>,
+}
+
+/// A page of entries, with the id of the next one.
+pub struct EntryPage {
+    pub entries: Vec<StreamEntry>,
+    pub next: Option<String>,
+}
+
+pub struct GroupInfo {
+    pub name: String,
+    pub consumers: usize,
+    pub pending: usize,
+    pub last_delivered_id: String,
+    /// Entries not delivered to the group yet, only known since Redis 7.
+    pub lag: Option<u64>,
+}
+
+pub struct ConsumerInfo {
+    pub name: String,
+    pub pending: usize,
+    /// Milliseconds since the consumer last read or claimed an entry.
+    pub idle: u64,
+}
+
+impl ConsumerInfo {
+    pub fn idle_as_human_delta(&self) -> String {
+        human_delta(self.idle / 1000)
+    }
+}
+
+pub struct PendingEntry {
+    pub id: String,
+    pub consumer: String,
+    /// Milliseconds since the entry was last delivered.
+    pub idle: u64,
+    pub deliveries: u64,
+}
+
+impl PendingEntry {
+    pub fn idle_as_human_delta(&self) -> String {
+        human_delta(self.idle / 1000)
+    }
+}
+
+/// The consumers of a group and the entries it has not acknowledged yet.
+pub struct GroupPending {
+    pub group: String,
+    pub consumers: Vec<ConsumerInfo>,
+    pub entries: Vec<PendingEntry>,
+}
+
+/// Reads the summary and first page of a stream, from the newest entry when `rev`.
+pub fn read_stream<C: ConnectionLike>(con: &mut C, key: &[u8], rev: bool) -> RedisResult<StreamValue> {
+    let info = read_info(con, key)?;
+    let page = read_entries(con, key, if rev { "+" } else { "-" }, rev)?;
+
+    Ok(StreamValue { info, entries: page.entries, next: page.next })
+}
+
+/// Reads a page of entries from the id `from` on, with XREVRANGE when `rev`.
+pub fn read_entries<C: ConnectionLike>(con: &mut C, key: &[u8], from: &str, rev: bool) -> RedisResult<EntryPage> {
+    let mut cmd = if rev { redis::cmd("XREVRANGE") } else { redis::cmd("XRANGE") };
+    cmd.arg(key)
+        .arg(from)
+        .arg(if rev { "-" } else { "+" })
+        .arg("COUNT")
+        .arg(PAGE);
+    let entries = parse_stream_entries(cmd.query(con)?)?;
+
+    // A short page is the end of the stream
+    let next = match entries.last() {
+        Some(last) if entries.len() == PAGE => next_id(&last.id, rev),
+        _ => None,
+    };
+    Ok(EntryPage { entries, next })
+}
+
+/// The id following `id`, or preceding it when `rev`. Paging from it works on the Redis
+/// versions without exclusive `(` ranges.
+pub fn next_id(id: &str, rev: bool) -> Option<String> {
+    let (ms, seq) = id.split_once('-')?;
+    let (ms, seq): (u64, u64) = (ms.parse().ok()?, seq.parse().ok()?);
+
+    let (ms, seq) = match (rev, seq) {
+        (false, u64::MAX) => (ms.checked_add(1)?, 0),
+        (false, seq) => (ms, seq + 1),
+        (true, 0) => (ms.checked_sub(1)?, u64::MAX),
+        (true, seq) => (ms, seq - 1),
+    };
+    Some(format!("{}-{}", ms, seq))
+}
+
+/// Parses `XRANGE` style replies, `[[id, [field, value, ...]], ...]`.
+pub fn parse_stream_entries(entries: Value) -> RedisResult<Vec<StreamEntry>> {
+    let entries: Vec<(String, Vec<Vec<u8>>)> = redis::from_owned_redis_value(entries)?;
+
+    Ok(entries.into_iter()
+        .map(|(id, fields)| StreamEntry {
+            id,
+            fields: fields.chunks(2)
+                .map(|pair| (pair[0].clone(), pair.get(1).cloned().unwrap_or_default()))
+                .collect(),
+        })
+        .collect())
+}
+
+pub fn read_info<C: ConnectionLike>(con: &mut C, key: &[u8]) -> RedisResult<StreamInfo> {
+    let info: HashMap<String, Value> = redis::cmd("XINFO").arg("STREAM").arg(key).query(con)?;
+    let entry_id = |name: &str| -> RedisResult<Option<String>> {
+        let entry: Option<(String, Value)> = field(&info, name)?;
+        Ok(entry.map(|(id, _)| id))
+    };
+
+    Ok(StreamInfo {
+        length: field(&info, "length")?,
+        groups: field(&info, "groups")?,
+        last_generated_id: field(&info, "last-generated-id")?,
+        first_id: entry_id("first-entry")?,
+        last_id: entry_id("last-entry")?,
+    })
+}
+
+pub fn read_groups<C: ConnectionLike>(con: &mut C, key: &[u8]) -> RedisResult<Vec<GroupInfo>> {
+    let groups: Vec<HashMap<String, Value>> = redis::cmd("XINFO").arg("GROUPS").arg(key).query(con)?;
+
+    groups.iter()
+        .map(|group| Ok(GroupInfo {
+            name: field(group, "name")?,
+            consumers: field(group, "consumers")?,
+            pending: field(group, "pending")?,
+            last_delivered_id: field(group, "last-delivered-id")?,
+            lag: field(group, "lag")?,
+        }))
+        .collect()
+}
+
+pub fn read_pending<C: ConnectionLike>(con: &mut C, key: &[u8], group: &str) -> RedisResult<GroupPending> {
+    let consumers: Vec<HashMap<String, Value>> = redis::cmd("XINFO")
+        .arg("CONSUMERS")
+        .arg(key)
+        .arg(group)
+        .query(con)?;
+    let consumers = consumers.iter()
+        .map(|consumer| Ok(ConsumerInfo {
+            name: field(consumer, "name")?,
+            pending: field(consumer, "pending")?,
+            idle: field(consumer, "idle")?,
+        }))
+        .collect::<RedisResult<_>>()?;
+
+    let entries: Vec<(String, String, u64, u64)> = redis::cmd("XPENDING")
+        .arg(key)
+        .arg(group)
+        .arg("-")
+        .arg("+")
+        .arg(PENDING_PAGE)
+        .query(con)?;
+    let entries = entries.into_iter()
+        .map(|(id, consumer, idle, deliveries)| PendingEntry { id, consumer, idle, deliveries })
+        .collect();
+
+    Ok(GroupPending { group: group.to_string(), consumers, entries })
+}
+
+/// A field of an `XINFO` reply, missing ones read as nil.
+fn field<T: FromRedisValue>(fields: &HashMap<String, Value>, name: &str) -> RedisResult<T> {
+    redis::from_redis_value(fields.get(name).unwrap_or(&Value::Nil))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_over_stream_ids() {
+        assert_eq!(next_id("1700000000000-4", false).as_deref(), Some("1700000000000-5"));
+        assert_eq!(next_id("1700000000000-4", true).as_deref(), Some("1700000000000-3"));
+        assert_eq!(next_id(&format!("5-{}", u64::MAX), false).as_deref(), Some("6-0"));
+        assert_eq!(next_id("5-0", true), Some(format!("4-{}", u64::MAX)));
+        assert_eq!(next_id("0-0", true), None);
+        assert_eq!(next_id("not an id", false), None);
+    }
+}
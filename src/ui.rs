@@ -1,12 +1,25 @@
-use serde_json::{Value, from_str, to_string_pretty};
 use tui::{
     backend::Backend,
-    widgets::{Block, Borders, Paragraph, Tabs, Table, Row, Wrap, block::Title, Clear},
+    widgets::{
+        Block, Borders, Paragraph, Tabs, Table, TableState, Row, Cell, Wrap, List, ListItem, Clear,
+        block::{Position, Title},
+    },
     layout::{Layout, Alignment, Constraint, Rect},
-    text::{Span, Spans}, Frame, style::{Style, Color},
+    text::{Span, Line, Text}, Frame, style::{Style, Color},
 };
+use regex::Regex;
+use serde_json::Value;
 
-use crate::app::{App, RedisServer};
+use crate::{
+    app::{App, KeyColumns, Prompt, RedisServer, RedisSession},
+    database::AddressConf,
+    decode,
+    error::AppError,
+    inspect::KeyInfo,
+    json_tree::{self, TreeNode},
+    server_manager::{FormField, ServerForm},
+    value::{self, escape, KeyValue, ViewedValue, ZRange},
+};
 
 fn ui_tabs<B>(f: &mut Frame<B>, area: Rect, app: &App)
 where
@@ -15,9 +28,15 @@ where
     let current_tab = app.current_tab();
     let servers = app.get_servers();
 
-    let titles = servers.into_iter().map(|s| {
-        Spans::from(Span::raw(s.name.clone()))
-    }).collect::<Vec<Spans>>();
+    let titles = servers.iter().map(|s| {
+        let mut spans = vec![
+            Span::styled(s.name.clone(), Style::default().fg(s.color().unwrap_or(Color::White))),
+        ];
+        if s.read_only {
+            spans.push(Span::styled(" RO", Style::default().fg(Color::Red)));
+        }
+        Line::from(spans)
+    }).collect::<Vec<Line>>();
 
     let tabs = Tabs::new(titles)
         .block(Block::default().borders(Borders::ALL).title(" Servers "))
@@ -28,78 +47,147 @@ where
     f.render_widget(tabs, area);
 }
 
-fn ui_server_disconnected<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer)
+fn ui_server_disconnected<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer, spinner: &str)
 where
     B: Backend,
 {
 
     let title = server.name.clone();
-    let host = server.host.clone();
+    let (address_label, address) = match &server.address {
+        AddressConf::Tcp { host, .. } => ("Host: ", host.clone()),
+        AddressConf::Unix { path } => ("Socket: ", path.display().to_string()),
+        AddressConf::Sentinel { .. } => ("Sentinel: ", server.address()),
+    };
+    let user = match (&server.username, server.has_password()) {
+        (Some(username), _) => username.clone(),
+        (None, true) => "default".to_string(),
+        (None, false) => "-".to_string(),
+    };
+    let db = server.db.to_string();
+    let tls = match &server.tls {
+        Some(tls) if tls.insecure => "on (insecure)",
+        Some(_) => "on",
+        None => "off",
+    };
 
-    let text = vec![
-        Spans::from(vec![
+    let mut text = vec![
+        Line::from(vec![
             Span::raw("Server is not connected"),
         ]),
-        Spans::default(),
-        Spans::from(vec![
-            Span::raw("Host: "),
-            Span::styled(host, Style::default().fg(Color::Yellow)),
+        Line::default(),
+        Line::from(vec![
+            Span::raw(address_label),
+            Span::styled(address, Style::default().fg(Color::Yellow)),
         ]),
-        Spans::default(),
-        Spans::from(vec![
-            Span::raw("Press "),
-            Span::styled("c", Style::default().fg(Color::Yellow)),
-            Span::raw(" to connect"),
+        Line::from(vec![
+            Span::raw("User: "),
+            Span::styled(user, Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(vec![
+            Span::raw("DB: "),
+            Span::styled(db, Style::default().fg(Color::Yellow)),
+            Span::raw("  TLS: "),
+            Span::styled(tls, Style::default().fg(Color::Yellow)),
+            Span::raw("  Cluster: "),
+            Span::styled(if server.cluster { "on" } else { "off" }, Style::default().fg(Color::Yellow)),
         ]),
+        Line::default(),
+        if server.is_connecting() {
+            Line::from(vec![
+                Span::raw(format!("{} Connecting, ", spinner)),
+                Span::styled("Esc", Style::default().fg(Color::Yellow)),
+                Span::raw(" to cancel"),
+            ])
+        } else {
+            Line::from(vec![
+                Span::raw("Press "),
+                Span::styled("c", Style::default().fg(Color::Yellow)),
+                Span::raw(" to connect"),
+            ])
+        },
     ];
 
+    if let Some(error) = &server.connect_error {
+        text.push(Line::default());
+        text.push(Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red))));
+    }
+
     let title = Paragraph::new(text)
         .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(Block::default().title(format!(" {} ", title)).borders(Borders::ALL));
 
     f.render_widget(title, area);
 }
 
-fn ui_server_connected<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer)
+/// ` ⠋ scanning ` while the session waits for the worker.
+fn ui_pending(session: &RedisSession, spinner: &str) -> String {
+    session.pending_label()
+        .map(|label| format!("{} {} ", spinner, label))
+        .unwrap_or_default()
+}
+
+fn ui_server_connected<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer, columns: KeyColumns, spinner: &str)
 where
     B: Backend,
 {
     let title = server.name.clone();
-    let host = server.host.clone();
+    let address = server.address();
 
-    let session = server
-        .get_session_mut()
-        .expect("Server is not connected");
+    let Some(session) = server.get_session_mut() else { return };
 
-    while !session.done() && area.height > session.count() as u16 {
-        session.next().expect("to get next");
-    }
+    // Keys are scanned in the background until this many rows are filled
+    session.page_height = area.height;
+    let pending = ui_pending(session, spinner);
 
     let filter = session.pattern.clone();
+    let db = if session.is_cluster() { "cluster".to_string() } else { format!("db {}", session.db) };
 
-    let widths = vec![
-        Constraint::Length(area.width - 8),
-        Constraint::Length(6),
-    ];
+    let mut header = vec!["Key"];
+    if columns.value_type {
+        header.push("Type");
+    }
+    if columns.size {
+        header.push("Size");
+    }
+    header.push("TTL");
+
+    // The key takes what the 6 wide columns after it leave
+    let others = header.len() as u16 - 1;
+    let mut widths = vec![Constraint::Length(area.width.saturating_sub(2 + others * 7))];
+    widths.extend((0..others).map(|_| Constraint::Length(6)));
 
     let key_list = Table::new(
         session.iter_keys()
-            .map(|(key, meta)| Row::new(vec![
-                key.to_string(),
-                meta.ttl_as_human_delta(),
-            ]))
+            .map(|(key, meta)| {
+                let mut row = vec![escape(key)];
+                if columns.value_type {
+                    row.push(meta.value_type());
+                }
+                if columns.size {
+                    row.push(meta.size_as_human());
+                }
+                row.push(meta.ttl_as_human_delta());
+                Row::new(row)
+            })
             .collect::<Vec<Row>>()
     )
     .header(
-        Row::new(vec!["Key", "TTL"])
+        Row::new(header)
             .style(Style::default().fg(Color::Yellow))
     )
     .block(
         Block::default()
-            .title(format!(" {} - {} ", title, host))
+            .title(format!(" {} - {} - {} {}", title, address, db, pending))
             .title(
                 Title::from(
-                    Spans::from(vec![
+                    Line::from(vec![
+                        Span::styled(" t", Style::default().fg(Color::Yellow)),
+                        Span::raw(" type"),
+                        Span::styled(" m", Style::default().fg(Color::Yellow)),
+                        Span::raw(" size"),
+                        Span::styled(" i", Style::default().fg(Color::Yellow)),
+                        Span::raw(" inspect"),
                         Span::styled(
                             " f ",
                             Style::default().fg(Color::Yellow)
@@ -117,50 +205,427 @@ where
     f.render_stateful_widget(key_list, area, &mut session.table_state);
 }
 
-fn ui_view_key<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer)
+fn ui_view_key<B>(f: &mut Frame<B>, area: Rect, server: &mut RedisServer, spinner: &str)
 where
     B: Backend,
 {
     let title = server.name.clone();
 
-    let session = server
-        .get_session_mut()
-        .expect("Server is not connected");
+    let Some(session) = server.get_session_mut() else { return };
+    let Some(key) = session.viewing_key.as_deref().map(escape) else { return };
+    // Collection members are paged in, and list elements read, for the rows on screen
+    session.page_height = area.height.saturating_sub(3);
+    session.view_width = area.width.saturating_sub(2);
+    let pending = ui_pending(session, spinner);
 
-    let key = session.viewing_key.clone().expect("to get viewing key");
-    let key_value = session.get_viewing_key().expect("to get viewing key value");
-    let key_value_pretty = {
-        let key_value_parsed: Value = from_str(key_value.as_str())
-            .unwrap_or(Value::String(key_value.clone()));
-        to_string_pretty(&key_value_parsed).unwrap_or(key_value.clone())
+    let Some(value) = &session.viewing_value else {
+        let loading = Paragraph::new(Line::from(vec![
+            Span::raw(pending),
+            Span::styled("Esc", Style::default().fg(Color::Yellow)),
+            Span::raw(" cancel"),
+        ]))
+            .alignment(Alignment::Center)
+            .block(Block::default().title(format!(" {} - {} ", title, key)).borders(Borders::ALL));
+        f.render_widget(loading, area);
+        return;
     };
 
-    let view = Paragraph::new(key_value_pretty)
-        .block(
-            Block::default()
-            .title(format!(" {} - {} ", title, key))
-            .borders(Borders::ALL)
-        )
-        .wrap(Wrap { trim: false })
-        .scroll((session.viewing_key_scroll, 0));
+    let auto_refresh = match session.auto_refresh {
+        Some(interval) => format!("every {}s", interval.as_secs()),
+        None => "off".to_string(),
+    };
+    let mut status = vec![
+        Span::raw(format!(" {}fetched {} ago ", pending, value.age_as_human_delta())),
+        Span::styled("r", Style::default().fg(Color::Yellow)),
+        Span::raw(" refresh "),
+        Span::styled("a", Style::default().fg(Color::Yellow)),
+        Span::raw(format!(" auto {} ", auto_refresh)),
+    ];
+    if value.members.is_some() {
+        status.push(Span::styled("f ", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(format!("{} ", session.member_pattern)));
+    }
+    if let KeyValue::ZSet(_) = value.value {
+        // Filtered members are scanned, in no particular order
+        let range = match &session.zset_range {
+            _ if session.member_pattern != "*" => "scanned".to_string(),
+            ZRange::Rank => "by rank".to_string(),
+            ZRange::Score(min, max) => format!("score {} {}", min, max),
+            ZRange::Lex(min, max) => format!("lex {} {}", min, max),
+        };
+        status.push(Span::styled("S L", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(format!(" {} ", range)));
+    }
+    if let KeyValue::Stream(_) = value.value {
+        status.push(Span::styled("C", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(" groups "));
+    }
+    if let KeyValue::ZSet(_) | KeyValue::Stream(_) = value.value {
+        status.push(Span::styled("o", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(if session.rev { " desc " } else { " asc " }));
+    }
+    if let KeyValue::String(_) = value.value {
+        status.push(Span::styled("x", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(if session.shows_hex_dump() { " text " } else { " hex " }));
+        status.push(Span::styled("D", Style::default().fg(Color::Yellow)));
+        let decoding = match (&session.rule, &session.decoders) {
+            (Some(rule), _) => format!(" rule {} ", rule.pattern),
+            (None, Some(_)) => " decoders ".to_string(),
+            (None, None) => " detect ".to_string(),
+        };
+        status.push(Span::raw(decoding));
+        if session.tree_nodes().is_none() {
+            status.push(Span::styled("w", Style::default().fg(Color::Yellow)));
+            status.push(Span::raw(if session.wrap { " nowrap " } else { " wrap " }));
+        }
+    }
+    if session.viewing_key_hscroll > 0 {
+        status.push(Span::styled("h l", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(format!(" col {} ", session.viewing_key_hscroll + 1)));
+    }
+    if let KeyValue::List(_) = value.value {
+        status.push(Span::styled("g G :", Style::default().fg(Color::Yellow)));
+        status.push(Span::raw(" jump "));
+    }
+    match &session.search {
+        Some(search) => {
+            status.push(Span::styled("/", Style::default().fg(Color::Yellow)));
+            status.push(Span::raw(format!("{} ", search)));
+            status.push(Span::styled("n N", Style::default().fg(Color::Yellow)));
+            status.push(Span::raw(" next "));
+        }
+        None => {
+            status.push(Span::styled("/", Style::default().fg(Color::Yellow)));
+            status.push(Span::raw(" search "));
+        }
+    }
+
+    // The decoders a string was run through, or why the chosen ones failed
+    let mut heading = vec![Span::raw(format!(" {} - {} - {} ", title, key, ui_value_summary(value)))];
+    if let Some(err) = &value.decode_error {
+        heading.push(Span::styled(format!("- {} ", err), Style::default().fg(Color::Red)));
+    } else if !value.decoders.is_empty() && !session.shows_hex_dump() {
+        heading.push(Span::styled(format!("- {} ", decode::chain_name(&value.decoders)), Style::default().fg(Color::Cyan)));
+    }
+
+    let block = Block::default()
+        .title(Line::from(heading))
+        .title(Title::from(Line::from(status)).alignment(Alignment::Right))
+        .borders(Borders::ALL);
+    let scroll = session.viewing_key_scroll;
+    let hscroll = session.viewing_key_hscroll;
+    let search = session.search.as_ref();
+
+    let (header, widths, rows): (Vec<&str>, Vec<Constraint>, Vec<Vec<String>>) = match &value.value {
+        KeyValue::String(_) => {
+            if let Some(nodes) = session.tree_nodes() {
+                let path = nodes.get(scroll).map(|node| node.path.clone()).unwrap_or_default();
+                let items: Vec<ListItem> = nodes.iter()
+                    .map(|node| ListItem::new(shift_line(ui_tree_node(node, search), hscroll)))
+                    .collect();
+                let hints = Line::from(vec![
+                    Span::raw(format!(" {} ", path)),
+                    Span::styled("y", Style::default().fg(Color::Yellow)),
+                    Span::raw(" copy path "),
+                    Span::styled("Enter", Style::default().fg(Color::Yellow)),
+                    Span::raw(" fold "),
+                ]);
+                let tree = List::new(items)
+                    .block(block.title(Title::from(hints).position(Position::Bottom)))
+                    .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(tui::style::Modifier::BOLD));
+
+                session.tree_state.select(Some(scroll));
+                f.render_stateful_widget(tree, area, &mut session.tree_state);
+                return;
+            }
+
+            // Wrapped here rather than by the paragraph, so the rows searches jump to are known. Only
+            // the lines on screen are highlighted and wrapped.
+            let height = area.height.saturating_sub(2) as usize;
+            let lines: Vec<Line> = if session.wraps() {
+                let width = session.view_width;
+                let Some(value) = session.viewing_value.as_mut() else { return };
+                let rows = value.wrapped_rows(width);
+                // The line the top row is part of
+                let first = rows.partition_point(|&row| row <= scroll).saturating_sub(1);
+                let skip = scroll - rows[first];
+                value.text_lines()
+                    .skip(first)
+                    .flat_map(|line| wrap_line(Line::from(highlight(line, search, Style::default())), width))
+                    .skip(skip)
+                    .take(height)
+                    .collect()
+            } else {
+                value.text_lines()
+                    .skip(scroll)
+                    .take(height)
+                    .map(|line| Line::from(highlight(line, search, Style::default())))
+                    .collect()
+            };
+            let view = Paragraph::new(lines)
+                .block(block)
+                .scroll((0, hscroll.min(u16::MAX as usize) as u16));
+            f.render_widget(view, area);
+            return;
+        }
+        KeyValue::Hash(fields) => (
+            vec!["Field", "Value"],
+            vec![Constraint::Percentage(30), Constraint::Percentage(70)],
+            fields.iter().map(|(field, value)| vec![escape(field), escape(value)]).collect(),
+        ),
+        KeyValue::List(window) => {
+            // Only the elements around the scrolled to index are read, JSON ones span several rows
+            let rows = window.elements.iter()
+                .enumerate()
+                .skip(scroll.saturating_sub(window.start))
+                .map(|(i, element)| {
+                    Row::new(vec![Cell::from((window.start + i).to_string()), Cell::from(shift_text(highlight_lines(element, search), hscroll))])
+                        .height(element.lines().count().max(1) as u16)
+                });
+            let table = Table::new(rows)
+                .header(Row::new(vec!["Index", "Element"]).style(Style::default().fg(Color::Yellow)))
+                .block(block)
+                .widths(&[Constraint::Length(10), Constraint::Percentage(100)]);
+            f.render_widget(table, area);
+            return;
+        }
+        KeyValue::Set(members) => (
+            vec!["Member"],
+            vec![Constraint::Percentage(100)],
+            members.iter().map(|member| vec![escape(member)]).collect(),
+        ),
+        KeyValue::ZSet(members) => (
+            vec!["Member", "Score"],
+            vec![Constraint::Percentage(75), Constraint::Percentage(25)],
+            members.iter().map(|(member, score)| vec![escape(member), score.to_string()]).collect(),
+        ),
+        KeyValue::Stream(stream) => {
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+            let chunks = Layout::default()
+                .direction(tui::layout::Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                .split(inner);
+
+            // The XINFO STREAM summary above the entries
+            let info = &stream.info;
+            let id = |id: &Option<String>| id.clone().unwrap_or_else(|| "-".to_string());
+            let summary = Paragraph::new(Line::from(vec![
+                Span::styled("first ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{}  ", id(&info.first_id))),
+                Span::styled("last ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{}  ", id(&info.last_id))),
+                Span::styled("last generated ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{}  ", info.last_generated_id)),
+                Span::styled("groups ", Style::default().fg(Color::Yellow)),
+                Span::raw(info.groups.to_string()),
+            ]));
+            f.render_widget(summary, chunks[0]);
+
+            let skip = scroll.min(stream.entries.len().saturating_sub(1));
+            let rows = stream.entries.iter()
+                .skip(skip)
+                .map(|entry| Row::new(vec![
+                    Cell::from(Line::from(highlight(&entry.id, search, Style::default()))),
+                    Cell::from(shift_line(Line::from(highlight(&entry.fields_as_text(), search, Style::default())), hscroll)),
+                ]));
+            let table = Table::new(rows)
+                .header(Row::new(vec!["ID", "Fields"]).style(Style::default().fg(Color::Yellow)))
+                .widths(&[Constraint::Length(24), Constraint::Percentage(100)]);
+            f.render_widget(table, chunks[1]);
+            return;
+        }
+    };
+
+    // Only the column holding the long members or values scrolls sideways
+    let scrolled_column = match value.value {
+        KeyValue::Set(_) | KeyValue::ZSet(_) => 0,
+        _ => 1,
+    };
+    let skip = scroll.min(rows.len().saturating_sub(1));
+    let rows = rows.into_iter()
+        .skip(skip)
+        .map(|row| Row::new(row.iter().enumerate().map(|(i, cell)| {
+            let line = Line::from(highlight(cell, search, Style::default()));
+            Cell::from(if i == scrolled_column { shift_line(line, hscroll) } else { line })
+        })));
+    let table = Table::new(rows)
+        .header(Row::new(header).style(Style::default().fg(Color::Yellow)))
+        .block(block)
+        .widths(&widths);
 
-    f.render_widget(view, area);
+    f.render_widget(table, area);
+}
+
+/// A row of the document tree: the node's key then its value, colored by type, or the size of a
+/// collapsed object or array.
+fn ui_tree_node(node: &TreeNode, search: Option<&Regex>) -> Line<'static> {
+    let marker = match (node.value, node.collapsed) {
+        (Value::Object(_) | Value::Array(_), false) => "▾ ",
+        (Value::Object(_) | Value::Array(_), true) => "▸ ",
+        _ => "  ",
+    };
+    let mut spans = vec![Span::raw(format!("{}{}", "  ".repeat(node.depth), marker))];
+    spans.extend(highlight(&node.label, search, Style::default().fg(Color::Blue)));
+
+    let dim = Style::default().fg(Color::DarkGray);
+    match node.value {
+        Value::Object(_) | Value::Array(_) if node.collapsed => {
+            let brackets = if node.value.is_object() { "{…}" } else { "[…]" };
+            let count = json_tree::child_count(node.value).unwrap_or_default();
+            spans.push(Span::styled(format!(" {} {}", brackets, count), dim));
+        }
+        Value::Object(_) | Value::Array(_) => {}
+        leaf => {
+            let color = match leaf {
+                Value::String(_) => Color::Green,
+                Value::Number(_) => Color::Magenta,
+                Value::Bool(_) => Color::Yellow,
+                _ => Color::DarkGray,
+            };
+            spans.push(Span::raw(": "));
+            spans.extend(highlight(&leaf.to_string(), search, Style::default().fg(color)));
+        }
+    }
+
+    Line::from(spans)
+}
+
+/// `line` without its first `columns` characters, for the value view scrolled sideways.
+fn shift_line(line: Line<'static>, mut columns: usize) -> Line<'static> {
+    if columns == 0 {
+        return line;
+    }
+
+    let mut spans = Vec::new();
+    for span in line.spans {
+        let len = span.content.chars().count();
+        if columns >= len {
+            columns -= len;
+            continue;
+        }
+        let content: String = span.content.chars().skip(columns).collect();
+        columns = 0;
+        spans.push(Span::styled(content, span.style));
+    }
+    Line::from(spans)
+}
+
+/// The rows `line` takes wrapped at `width` columns, as the value view scrolls through them.
+fn wrap_line(line: Line<'static>, width: u16) -> Vec<Line<'static>> {
+    let text: String = line.spans.iter().map(|span| span.content.as_ref()).collect();
+    let mut offsets = value::wrap_offsets(&text, width as usize).into_iter().skip(1).peekable();
+
+    let mut rows = vec![Vec::new()];
+    let mut start = 0;
+    for span in line.spans {
+        let end = start + span.content.len();
+        let mut from = start;
+        while let Some(cut) = offsets.next_if(|&cut| cut < end) {
+            if cut > from {
+                rows.last_mut().unwrap().push(Span::styled(span.content[from - start..cut - start].to_string(), span.style));
+            }
+            rows.push(Vec::new());
+            from = cut;
+        }
+        if end > from {
+            rows.last_mut().unwrap().push(Span::styled(span.content[from - start..].to_string(), span.style));
+        }
+        start = end;
+    }
+    rows.into_iter().map(Line::from).collect()
+}
+
+/// The lines of `text` each scrolled sideways by `columns`.
+fn shift_text(text: Text<'static>, columns: usize) -> Text<'static> {
+    Text::from(text.lines.into_iter().map(|line| shift_line(line, columns)).collect::<Vec<_>>())
+}
+
+/// `text` in `style`, with the matches of the value view search highlighted.
+fn highlight(text: &str, search: Option<&Regex>, style: Style) -> Vec<Span<'static>> {
+    let Some(search) = search else { return vec![Span::styled(text.to_string(), style)] };
+
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for found in search.find_iter(text) {
+        spans.push(Span::styled(text[end..found.start()].to_string(), style));
+        spans.push(Span::styled(found.as_str().to_string(), style.fg(Color::Black).bg(Color::Yellow)));
+        end = found.end();
+    }
+    spans.push(Span::styled(text[end..].to_string(), style));
+    spans
+}
+
+/// The lines of `text` with the search matches highlighted.
+fn highlight_lines(text: &str, search: Option<&Regex>) -> Text<'static> {
+    Text::from(text.lines().map(|line| Line::from(highlight(line, search, Style::default()))).collect::<Vec<_>>())
+}
+
+/// `hash, 12 fields` style summary shown in the value view title, `hash, 20 of 5000 fields` while
+/// members or entries are being paged in or filtered.
+fn ui_value_summary(viewed: &ViewedValue) -> String {
+    let value = &viewed.value;
+    let (count, unit) = match value {
+        KeyValue::String(raw) => (raw.len(), "bytes"),
+        KeyValue::Hash(fields) => (fields.len(), "fields"),
+        KeyValue::List(window) => (window.len, "elements"),
+        KeyValue::Set(members) => (members.len(), "members"),
+        KeyValue::ZSet(members) => (members.len(), "members"),
+        KeyValue::Stream(stream) => (stream.entries.len(), "entries"),
+    };
+    let total = match value {
+        KeyValue::Stream(stream) => Some(stream.info.length),
+        _ => viewed.members.as_ref().map(|members| members.len),
+    };
+
+    match total {
+        Some(total) if total != count => format!("{}, {} of {} {}", value.type_name(), count, total, unit),
+        _ => format!("{}, {} {}", value.type_name(), count, unit),
+    }
+}
+
+fn ui_no_servers<B>(f: &mut Frame<B>, area: Rect, app: &App)
+where
+    B: Backend,
+{
+    let config_path = app.config_path.display().to_string();
+
+    let text = vec![
+        Line::from(vec![
+            Span::raw("No servers configured"),
+        ]),
+        Line::default(),
+        Line::from(vec![
+            Span::raw("Press "),
+            Span::styled("s", Style::default().fg(Color::Yellow)),
+            Span::raw(" to add a server, or edit "),
+            Span::styled(config_path, Style::default().fg(Color::Yellow)),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+
+    f.render_widget(paragraph, area);
 }
 
 fn ui_server<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
 where
     B: Backend,
 {
-    let server = app.get_current_server_mut();
+    let spinner = app.spinner();
+    let columns = app.key_columns;
+    let Some(server) = app.get_current_server_mut() else {
+        ui_no_servers(f, area, app);
+        return;
+    };
 
-    if server.is_connected() {
-        if !server.get_session().expect("to get session").viewing_key.is_none() {
-            ui_view_key(f, area, server);
-        } else {
-            ui_server_connected(f, area, server);
-        }
-    } else {
-        ui_server_disconnected(f, area, server);
+    let viewing_key = server.get_session().is_some_and(|session| session.viewing_key.is_some());
+    match (server.is_connected(), viewing_key) {
+        (true, true) => ui_view_key(f, area, server, spinner),
+        (true, false) => ui_server_connected(f, area, server, columns, spinner),
+        (false, _) => ui_server_disconnected(f, area, server, spinner),
     }
 }
 
@@ -171,6 +636,37 @@ where
     ui_server(f, area, app);
 }
 
+fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
+    let vert = Layout::default()
+        .direction(tui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(50),
+            Constraint::Length(height),
+            Constraint::Percentage(50),
+        ].as_ref())
+        .split(area);
+
+    Layout::default()
+        .direction(tui::layout::Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ].as_ref())
+        .split(vert[1])[1]
+}
+
+fn ui_prompt<B>(f: &mut Frame<B>, area: Rect, prompt: &Prompt)
+where
+    B: Backend,
+{
+    let paragraph = Paragraph::new(prompt.input.clone())
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", prompt.kind.title())));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area)
+}
+
 fn ui_filter<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
 where
     B: Backend,
@@ -184,6 +680,339 @@ where
     f.render_widget(paragraph, area)
 }
 
+fn ui_server_form<B>(f: &mut Frame<B>, area: Rect, form: &ServerForm)
+where
+    B: Backend,
+{
+    let mut text = FormField::ALL.iter().map(|field| {
+        let style = if *field == form.focused_field() {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+
+        Line::from(vec![
+            Span::styled(format!("{:>10}: ", field.label()), style),
+            Span::raw(form.value(*field)),
+        ])
+    }).collect::<Vec<Line>>();
+
+    text.push(Line::default());
+    text.push(match &form.status {
+        Some(Ok(message)) => Line::from(Span::styled(message.clone(), Style::default().fg(Color::Green))),
+        Some(Err(message)) => Line::from(Span::styled(message.clone(), Style::default().fg(Color::Red))),
+        None => Line::default(),
+    });
+    text.push(Line::default());
+    text.push(Line::from(vec![
+        Span::styled("Tab", Style::default().fg(Color::Yellow)),
+        Span::raw(" move  "),
+        Span::styled("Space", Style::default().fg(Color::Yellow)),
+        Span::raw(" toggle  "),
+        Span::styled("^t", Style::default().fg(Color::Yellow)),
+        Span::raw(" test  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" save  "),
+        Span::styled("Esc", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]));
+
+    let title = match form.editing {
+        Some(_) => " Edit server ",
+        None => " Add server ",
+    };
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn ui_server_manager<B>(f: &mut Frame<B>, area: Rect, app: &App)
+where
+    B: Backend,
+{
+    let manager = app.server_manager.as_ref().expect("server manager to be open");
+
+    if let Some(form) = &manager.form {
+        ui_server_form(f, area, form);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(tui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(3),
+        ].as_ref())
+        .split(area);
+
+    let rows = app.get_servers().iter().map(|server| {
+        Row::new(vec![
+            Span::styled(server.name.clone(), Style::default().fg(server.color().unwrap_or(Color::White))),
+            Span::raw(server.address()),
+            Span::raw(if server.read_only { "RO" } else { "" }),
+        ])
+    }).collect::<Vec<Row>>();
+
+    let widths = [
+        Constraint::Percentage(35),
+        Constraint::Percentage(55),
+        Constraint::Percentage(10),
+    ];
+
+    let mut table_state = TableState::default();
+    table_state.select((!app.get_servers().is_empty()).then_some(manager.selected));
+
+    let table = Table::new(rows)
+        .block(Block::default().borders(Borders::ALL).title(" Servers "))
+        .widths(&widths)
+        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(tui::style::Modifier::BOLD));
+
+    let footer = match (&manager.status, manager.confirm_delete) {
+        (_, true) => Line::from(vec![
+            Span::raw("Press "),
+            Span::styled("x", Style::default().fg(Color::Yellow)),
+            Span::raw(" again to delete the selected server"),
+        ]),
+        (Some(status), false) => Line::from(Span::styled(status.clone(), Style::default().fg(Color::Red))),
+        (None, false) => Line::from(vec![
+            Span::styled("a", Style::default().fg(Color::Yellow)),
+            Span::raw(" add  "),
+            Span::styled("e", Style::default().fg(Color::Yellow)),
+            Span::raw(" edit  "),
+            Span::styled("y", Style::default().fg(Color::Yellow)),
+            Span::raw(" duplicate  "),
+            Span::styled("x", Style::default().fg(Color::Yellow)),
+            Span::raw(" delete  "),
+            Span::styled("Esc", Style::default().fg(Color::Yellow)),
+            Span::raw(" close"),
+        ]),
+    };
+
+    let footer = Paragraph::new(footer)
+        .block(Block::default().borders(Borders::ALL));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(table, chunks[0], &mut table_state);
+    f.render_widget(footer, chunks[1]);
+}
+
+fn ui_keyspace_overview<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let overview = app.keyspace_overview.as_mut().expect("keyspace overview to be open");
+
+    let rows = overview.databases.iter().map(|info| {
+        Row::new(vec![
+            format!("db{}", info.db),
+            info.keys.to_string(),
+            info.expires.to_string(),
+            info.avg_ttl_as_human_delta(),
+        ])
+    }).collect::<Vec<Row>>();
+
+    let widths = [
+        Constraint::Percentage(25),
+        Constraint::Percentage(25),
+        Constraint::Percentage(25),
+        Constraint::Percentage(25),
+    ];
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["DB", "Keys", "Expires", "Avg TTL"])
+                .style(Style::default().fg(Color::Yellow))
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Databases ")
+                .title(
+                    Title::from(Line::from(vec![
+                        Span::styled(" Enter", Style::default().fg(Color::Yellow)),
+                        Span::raw(" select "),
+                    ])).alignment(Alignment::Right)
+                )
+        )
+        .widths(&widths)
+        .highlight_style(Style::default().fg(Color::Cyan).add_modifier(tui::style::Modifier::BOLD));
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(table, area, &mut overview.table_state);
+}
+
+fn ui_key_inspector<B>(f: &mut Frame<B>, area: Rect, info: &KeyInfo)
+where
+    B: Backend,
+{
+    // Redis only tracks the idle time or the access frequency, as the eviction policy needs
+    let rows = vec![
+        ("Type", info.value_type.clone()),
+        ("Encoding", info.encoding.clone().unwrap_or_else(|| "-".to_string())),
+        ("Length", info.length_as_human()),
+        ("Memory", info.memory_as_human()),
+        ("Serialized", info.serialized_as_human()),
+        ("Expires", info.expiry_as_human()),
+        ("Idle", info.idle_as_human_delta()),
+        ("Frequency", info.freq.map_or_else(|| "-".to_string(), |freq| freq.to_string())),
+    ];
+
+    let table = Table::new(
+        rows.into_iter()
+            .map(|(name, value)| Row::new(vec![
+                Cell::from(Span::styled(name, Style::default().fg(Color::Yellow))),
+                Cell::from(value),
+            ]))
+            .collect::<Vec<Row>>()
+    )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", escape(&info.key)))
+                .title(
+                    Title::from(Line::from(vec![
+                        Span::styled(" Esc", Style::default().fg(Color::Yellow)),
+                        Span::raw(" close "),
+                    ])).alignment(Alignment::Right)
+                )
+        )
+        .widths(&[Constraint::Length(12), Constraint::Percentage(100)]);
+
+    f.render_widget(Clear, area);
+    f.render_widget(table, area);
+}
+
+fn ui_error<B>(f: &mut Frame<B>, area: Rect, error: &AppError)
+where
+    B: Backend,
+{
+    let text = vec![
+        Line::from(Span::styled(error.to_string(), Style::default().fg(Color::Red))),
+        Line::default(),
+        Line::from(vec![
+            Span::raw("Press any key to dismiss"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title(" Error ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn ui_stream_groups<B>(f: &mut Frame<B>, area: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let groups = app.stream_groups.as_mut().expect("stream groups to be open");
+    f.render_widget(Clear, area);
+
+    let Some(pending) = &groups.pending else {
+        let rows = groups.groups.iter().map(|group| {
+            Row::new(vec![
+                group.name.clone(),
+                group.consumers.to_string(),
+                group.pending.to_string(),
+                group.last_delivered_id.clone(),
+                group.lag.map_or("N/A".to_string(), |lag| lag.to_string()),
+            ])
+        }).collect::<Vec<Row>>();
+
+        let widths = [
+            Constraint::Percentage(30),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(30),
+            Constraint::Percentage(16),
+        ];
+
+        let table = Table::new(rows)
+            .header(
+                Row::new(vec!["Group", "Consumers", "Pending", "Last delivered", "Lag"])
+                    .style(Style::default().fg(Color::Yellow))
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Consumer groups of {} ", escape(&groups.key)))
+                    .title(
+                        Title::from(Line::from(vec![
+                            Span::styled(" Enter", Style::default().fg(Color::Yellow)),
+                            Span::raw(" pending entries "),
+                        ])).alignment(Alignment::Right)
+                    )
+            )
+            .widths(&widths)
+            .highlight_style(Style::default().fg(Color::Cyan).add_modifier(tui::style::Modifier::BOLD));
+
+        f.render_stateful_widget(table, area, &mut groups.table_state);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(tui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Length(pending.consumers.len() as u16 + 3),
+            Constraint::Min(0),
+        ].as_ref())
+        .split(area);
+
+    let consumers = Table::new(
+        pending.consumers.iter()
+            .map(|consumer| Row::new(vec![
+                consumer.name.clone(),
+                consumer.pending.to_string(),
+                consumer.idle_as_human_delta(),
+            ]))
+            .collect::<Vec<Row>>()
+    )
+        .header(Row::new(vec!["Consumer", "Pending", "Idle"]).style(Style::default().fg(Color::Yellow)))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Consumers of {} ", pending.group))
+                .title(
+                    Title::from(Line::from(vec![
+                        Span::styled(" Esc", Style::default().fg(Color::Yellow)),
+                        Span::raw(" back "),
+                    ])).alignment(Alignment::Right)
+                )
+        )
+        .widths(&[Constraint::Percentage(50), Constraint::Percentage(25), Constraint::Percentage(25)]);
+    f.render_widget(consumers, chunks[0]);
+
+    let entries = Table::new(
+        pending.entries.iter()
+            .map(|entry| Row::new(vec![
+                entry.id.clone(),
+                entry.consumer.clone(),
+                entry.idle_as_human_delta(),
+                entry.deliveries.to_string(),
+            ]))
+            .collect::<Vec<Row>>()
+    )
+        .header(Row::new(vec!["ID", "Consumer", "Idle", "Deliveries"]).style(Style::default().fg(Color::Yellow)))
+        .block(Block::default().borders(Borders::ALL).title(" Pending entries "))
+        .widths(&[
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ]);
+    f.render_widget(entries, chunks[1]);
+}
+
 pub fn ui<B>(f: &mut Frame<B>, app: &mut App)
 where
     B: Backend,
@@ -203,25 +1032,30 @@ where
 
     if app.entering_filter {
         // Show filter input on top of everything
-        //   Create layout for centered box
-        let vert = Layout::default()
-            .direction(tui::layout::Direction::Vertical)
-            .constraints([
-                Constraint::Percentage(50),
-                Constraint::Length(3),
-                Constraint::Percentage(50),
-            ].as_ref())
-            .split(size);
-
-        let chunk = Layout::default()
-            .direction(tui::layout::Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(25),
-                Constraint::Percentage(50),
-                Constraint::Percentage(25),
-            ].as_ref())
-            .split(vert[1])[1];
-
-        ui_filter(f, chunk, app);
+        ui_filter(f, centered_rect(50, 3, size), app);
+    }
+
+    if let Some(prompt) = &app.prompt {
+        ui_prompt(f, centered_rect(50, 3, size), prompt);
+    }
+
+    if app.stream_groups.is_some() {
+        ui_stream_groups(f, centered_rect(80, 20, size), app);
+    }
+
+    if app.keyspace_overview.is_some() {
+        ui_keyspace_overview(f, centered_rect(50, 20, size), app);
+    }
+
+    if let Some(info) = &app.key_inspector {
+        ui_key_inspector(f, centered_rect(60, 10, size), info);
+    }
+
+    if app.server_manager.is_some() {
+        ui_server_manager(f, centered_rect(60, 25, size), app);
+    }
+
+    if let Some(error) = &app.error {
+        ui_error(f, centered_rect(60, 7, size), error);
     }
 }
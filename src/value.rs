@@ -0,0 +1,624 @@
+use std::{ops::Range, time::Instant};
+use redis::{self, ConnectionLike, RedisResult, Value};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    app::human_delta,
+    decode::{self, Decoded, Decoder},
+    stream::{self, StreamValue},
+};
+
+/// A key's value, read with the commands matching its TYPE. Values are kept as the bytes Redis
+/// holds, they are only turned into text to be shown.
+pub enum KeyValue {
+    String(Vec<u8>),
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+    List(ListWindow),
+    Set(Vec<Vec<u8>>),
+    ZSet(Vec<(Vec<u8>, f64)>),
+    Stream(StreamValue),
+}
+
+impl KeyValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            KeyValue::String(_) => "string",
+            KeyValue::Hash(_) => "hash",
+            KeyValue::List(_) => "list",
+            KeyValue::Set(_) => "set",
+            KeyValue::ZSet(_) => "zset",
+            KeyValue::Stream(_) => "stream",
+        }
+    }
+
+    /// Elements held, members loaded so far for a paged collection.
+    pub fn loaded(&self) -> usize {
+        match self {
+            KeyValue::String(_) => 1,
+            KeyValue::Hash(fields) => fields.len(),
+            KeyValue::List(window) => window.elements.len(),
+            KeyValue::Set(members) => members.len(),
+            KeyValue::ZSet(members) => members.len(),
+            KeyValue::Stream(stream) => stream.entries.len(),
+        }
+    }
+
+    /// Appends a page of members read from the same collection.
+    fn extend(&mut self, page: KeyValue) {
+        match (self, page) {
+            (KeyValue::Hash(fields), KeyValue::Hash(page)) => fields.extend(page),
+            (KeyValue::Set(members), KeyValue::Set(page)) => members.extend(page),
+            (KeyValue::ZSet(members), KeyValue::ZSet(page)) => members.extend(page),
+            _ => {}
+        }
+    }
+}
+
+/// The part of a list around what is on screen, long lists are never read whole.
+pub struct ListWindow {
+    /// Elements in the whole list.
+    pub len: usize,
+    /// Index of the first element read.
+    pub start: usize,
+    /// Elements from `start`, pretty-printed when they hold JSON and escaped otherwise.
+    pub elements: Vec<String>,
+}
+
+impl ListWindow {
+    /// Whether the `count` elements from `start` are read, or those up to the end of the list.
+    pub fn covers(&self, start: usize, count: usize) -> bool {
+        let end = (start + count).min(self.len);
+        self.start <= start && end <= self.start + self.elements.len()
+    }
+
+    /// The element `index` names, counting back from the end when negative, `None` when the list
+    /// has no such element.
+    pub fn index(&self, index: &str) -> Option<usize> {
+        let len = self.len as i64;
+        let element = match index.trim().parse::<i64>().ok()? {
+            element if element < 0 => len + element,
+            element => element,
+        };
+        (0..len).contains(&element).then_some(element as usize)
+    }
+}
+
+/// Members of a sorted set read per page of a range.
+const ZSET_PAGE: usize = 100;
+
+/// The members of a sorted set to show, in order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZRange {
+    /// Every member, by rank.
+    Rank,
+    /// Members scored between ZRANGEBYSCORE bounds, like `(1.5` or `+inf`.
+    Score(String, String),
+    /// Members between ZRANGEBYLEX bounds, like `[a` or `-`.
+    Lex(String, String),
+}
+
+impl ZRange {
+    /// Parses `min max` score bounds, an empty input for every member.
+    pub fn parse_score(input: &str) -> Option<ZRange> {
+        let is_bound = |bound: &str| {
+            let bound = bound.strip_prefix('(').unwrap_or(bound);
+            matches!(bound, "-inf" | "+inf" | "inf") || bound.parse::<f64>().is_ok()
+        };
+
+        ZRange::parse_bounds(input, is_bound, ZRange::Score)
+    }
+
+    /// Parses `min max` lexicographical bounds, an empty input for every member.
+    pub fn parse_lex(input: &str) -> Option<ZRange> {
+        let is_bound = |bound: &str| matches!(bound, "-" | "+") || bound.starts_with('[') || bound.starts_with('(');
+
+        ZRange::parse_bounds(input, is_bound, ZRange::Lex)
+    }
+
+    fn parse_bounds(
+        input: &str,
+        is_bound: impl Fn(&str) -> bool,
+        range: fn(String, String) -> ZRange,
+    ) -> Option<ZRange> {
+        let bounds: Vec<&str> = input.split_whitespace().collect();
+        match bounds[..] {
+            [] => Some(ZRange::Rank),
+            [min, max] if is_bound(min) && is_bound(max) => Some(range(min.to_string(), max.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Which part of a value to read, for the collections too large to read whole.
+#[derive(Clone)]
+pub struct ValueQuery {
+    /// MATCH pattern for the members of hashes, sets and sorted sets.
+    pub pattern: String,
+    /// Elements of a list to read.
+    pub window: Range<usize>,
+    /// Members of a sorted set to read, unless they are filtered by `pattern` with ZSCAN.
+    pub zrange: ZRange,
+    /// Read sorted sets from the highest score down, streams from the newest entry.
+    pub rev: bool,
+    /// Decoders to run strings through, `None` to use the ones they look encoded with.
+    pub decoders: Option<Vec<Decoder>>,
+}
+
+impl ValueQuery {
+    /// Whether a sorted set is read by range, in order, rather than scanned.
+    fn by_range(&self) -> bool {
+        self.pattern == "*"
+    }
+}
+
+/// Collections too large to read whole, paged through with their *SCAN command.
+#[derive(Clone, Copy)]
+pub enum Collection {
+    Hash,
+    Set,
+    ZSet,
+}
+
+impl Collection {
+    fn scan_command(&self) -> &'static str {
+        match self {
+            Collection::Hash => "HSCAN",
+            Collection::Set => "SSCAN",
+            Collection::ZSet => "ZSCAN",
+        }
+    }
+
+    fn len_command(&self) -> &'static str {
+        match self {
+            Collection::Hash => "HLEN",
+            Collection::Set => "SCARD",
+            Collection::ZSet => "ZCARD",
+        }
+    }
+}
+
+/// Position of the paging through a viewed collection.
+pub struct MemberScan {
+    pub collection: Collection,
+    /// The *SCAN cursor, or the offset of the next page of a sorted set range.
+    pub cursor: u64,
+    pub done: bool,
+    /// Members in the whole collection, matching the pattern or not.
+    pub len: usize,
+}
+
+/// A page of members, with the cursor of the next one.
+pub struct MemberPage {
+    pub cursor: u64,
+    pub len: usize,
+    pub members: KeyValue,
+}
+
+/// Reads `key` according to its TYPE, `None` when it does not exist. Hashes, sets and sorted
+/// sets only get their first page of members, lists the elements in the query's window.
+pub fn fetch<C: ConnectionLike>(con: &mut C, key: &[u8], query: &ValueQuery) -> RedisResult<Option<ViewedValue>> {
+    let value_type: String = redis::cmd("TYPE").arg(key).query(con)?;
+
+    let collection = match value_type.as_str() {
+        "hash" => Collection::Hash,
+        "set" => Collection::Set,
+        "zset" => Collection::ZSet,
+        _ => {
+            let value = match value_type.as_str() {
+                "none" => return Ok(None),
+                "string" => {
+                    let value: Option<Vec<u8>> = redis::cmd("GET").arg(key).query(con)?;
+                    match value {
+                        Some(value) => KeyValue::String(value),
+                        None => return Ok(None),
+                    }
+                }
+                "list" => {
+                    let window = read_list(con, key, query.window.clone())?;
+                    if window.len == 0 {
+                        return Ok(None);
+                    }
+                    KeyValue::List(window)
+                }
+                "stream" => KeyValue::Stream(stream::read_stream(con, key, query.rev)?),
+                other => return Err(redis::RedisError::from((
+                    redis::ErrorKind::TypeError,
+                    "Unsupported key type",
+                    other.to_string(),
+                ))),
+            };
+            return Ok(Some(ViewedValue::new(value, query.decoders.as_deref())));
+        }
+    };
+
+    let page = read_members(con, key, collection, 0, query)?;
+    if page.len == 0 {
+        // Emptied collections are deleted
+        return Ok(None);
+    }
+
+    let mut value = ViewedValue::new(page.members, None);
+    value.members = Some(MemberScan { collection, cursor: page.cursor, done: page.cursor == 0, len: page.len });
+    Ok(Some(value))
+}
+
+/// Reads the elements of a list in `window` with LRANGE, and its length.
+pub fn read_list<C: ConnectionLike>(con: &mut C, key: &[u8], window: Range<usize>) -> RedisResult<ListWindow> {
+    let len: usize = redis::cmd("LLEN").arg(key).query(con)?;
+    let elements: Vec<Vec<u8>> = if window.is_empty() {
+        Vec::new()
+    } else {
+        redis::cmd("LRANGE").arg(key).arg(window.start).arg(window.end - 1).query(con)?
+    };
+
+    Ok(ListWindow {
+        len,
+        start: window.start,
+        elements: elements.into_iter()
+            .map(|element| pretty_json(&element).unwrap_or_else(|| escape(&element)))
+            .collect(),
+    })
+}
+
+/// Reads the page of members at `cursor` with HSCAN, SSCAN or ZSCAN, or the next page of the
+/// range of a sorted set.
+pub fn read_members<C: ConnectionLike>(
+    con: &mut C,
+    key: &[u8],
+    collection: Collection,
+    cursor: u64,
+    query: &ValueQuery,
+) -> RedisResult<MemberPage> {
+    let len: usize = redis::cmd(collection.len_command()).arg(key).query(con)?;
+
+    if let (Collection::ZSet, true) = (collection, query.by_range()) {
+        return read_zrange(con, key, cursor as usize, len, query);
+    }
+
+    let mut cmd = redis::cmd(collection.scan_command());
+    cmd.arg(key)
+        .cursor_arg(cursor)
+        .arg("MATCH")
+        .arg(&query.pattern);
+    let (cursor, members): (u64, Value) = cmd.query(con)?;
+
+    let members = match collection {
+        Collection::Hash => KeyValue::Hash(redis::from_owned_redis_value(members)?),
+        Collection::Set => KeyValue::Set(redis::from_owned_redis_value(members)?),
+        Collection::ZSet => KeyValue::ZSet(redis::from_owned_redis_value(members)?),
+    };
+
+    Ok(MemberPage { cursor, len, members })
+}
+
+/// Reads the page of a sorted set range at `offset`, the cursor of the next page being its offset.
+fn read_zrange<C: ConnectionLike>(
+    con: &mut C,
+    key: &[u8],
+    offset: usize,
+    len: usize,
+    query: &ValueQuery,
+) -> RedisResult<MemberPage> {
+    // A short page is the end of the range
+    let page = |read: usize, members| {
+        let cursor = if read < ZSET_PAGE { 0 } else { (offset + ZSET_PAGE) as u64 };
+        MemberPage { cursor, len, members: KeyValue::ZSet(members) }
+    };
+
+    let (command, bounds) = match &query.zrange {
+        ZRange::Rank => (if query.rev { "ZREVRANGE" } else { "ZRANGE" }, None),
+        ZRange::Score(min, max) => (if query.rev { "ZREVRANGEBYSCORE" } else { "ZRANGEBYSCORE" }, Some((min, max))),
+        ZRange::Lex(min, max) => (if query.rev { "ZREVRANGEBYLEX" } else { "ZRANGEBYLEX" }, Some((min, max))),
+    };
+
+    let mut cmd = redis::cmd(command);
+    cmd.arg(key);
+    match bounds {
+        // The REV commands take the highest bound first
+        Some((min, max)) if query.rev => cmd.arg(max).arg(min).arg("LIMIT").arg(offset).arg(ZSET_PAGE),
+        Some((min, max)) => cmd.arg(min).arg(max).arg("LIMIT").arg(offset).arg(ZSET_PAGE),
+        None => cmd.arg(offset).arg(offset + ZSET_PAGE - 1),
+    };
+
+    if !matches!(query.zrange, ZRange::Lex(..)) {
+        let members: Vec<(Vec<u8>, f64)> = cmd.arg("WITHSCORES").query(con)?;
+        return Ok(page(members.len(), members));
+    }
+
+    // ZRANGEBYLEX has no WITHSCORES, the scores are read with ZMSCORE
+    let members: Vec<Vec<u8>> = cmd.query(con)?;
+    if members.is_empty() {
+        return Ok(page(0, Vec::new()));
+    }
+    let scores: Vec<Option<f64>> = match redis::cmd("ZMSCORE").arg(key).arg(&members).query(con) {
+        Ok(scores) => scores,
+        // Before Redis 6.2, in one pipeline, or one by one on a cluster, which cannot pipeline
+        Err(err) if is_unknown_command(&err) && con.supports_pipelining() => {
+            let mut pipe = redis::pipe();
+            for member in &members {
+                pipe.cmd("ZSCORE").arg(key).arg(member);
+            }
+            pipe.query(con)?
+        }
+        Err(err) if is_unknown_command(&err) => members.iter()
+            .map(|member| redis::cmd("ZSCORE").arg(key).arg(member).query(con))
+            .collect::<RedisResult<_>>()?,
+        Err(err) => return Err(err),
+    };
+
+    let read = members.len();
+    let members = members.into_iter()
+        .zip(scores)
+        // Skip the members removed since the range was read
+        .filter_map(|(member, score)| Some((member, score?)))
+        .collect();
+    Ok(page(read, members))
+}
+
+/// Whether `err` is the reply of a server too old for the command.
+fn is_unknown_command(err: &redis::RedisError) -> bool {
+    err.code() == Some("ERR") && err.detail().is_some_and(|detail| detail.starts_with("unknown command"))
+}
+
+/// A fetched value and the form it is shown in, kept until the value is refreshed.
+pub struct ViewedValue {
+    pub value: KeyValue,
+    /// Strings as text, decoded and pretty-printed when they hold a document.
+    pub pretty: Option<String>,
+    /// `hexdump -C` style lines of a string, while it is shown as a hex dump.
+    pub hex_dump: Option<Vec<String>>,
+    /// The object or array a string holds, shown as a tree.
+    pub document: Option<serde_json::Value>,
+    /// Whether a string shown as stored is binary rather than text, it is shown as a hex dump
+    /// then.
+    pub binary: bool,
+    /// Decoders a string was run through, empty when it is shown as stored.
+    pub decoders: Vec<Decoder>,
+    /// Why a string could not be decoded with the chosen decoders, it is shown as stored then.
+    pub decode_error: Option<String>,
+    /// Paging state of a hash, set or sorted set, whose members are loaded as they are scrolled to.
+    pub members: Option<MemberScan>,
+    pub fetched_at: Instant,
+    /// The width the text lines were last wrapped at, and the rows they start at then.
+    wrapped: Option<(u16, Vec<usize>)>,
+}
+
+impl ViewedValue {
+    /// Strings are run through `decoders`, or the ones they look encoded with when `None`.
+    pub fn new(value: KeyValue, decoders: Option<&[Decoder]>) -> ViewedValue {
+        let mut viewed = ViewedValue {
+            value,
+            pretty: None,
+            hex_dump: None,
+            document: None,
+            binary: false,
+            decoders: Vec::new(),
+            decode_error: None,
+            members: None,
+            fetched_at: Instant::now(),
+            wrapped: None,
+        };
+
+        if let KeyValue::String(raw) = &viewed.value {
+            let decoded = match decoders {
+                Some(decoders) => decode::decode(raw, decoders).unwrap_or_else(|err| {
+                    viewed.decode_error = Some(err);
+                    Decoded::bytes(Vec::new(), raw)
+                }),
+                None => decode::detect(raw),
+            };
+            viewed.binary = decoded.chain.is_empty() && is_binary(raw);
+            viewed.pretty = Some(decoded.text);
+            viewed.document = decoded.document;
+            viewed.decoders = decoded.chain;
+        }
+        viewed
+    }
+
+    /// Dumps a string as hex when `shown`, once until it is hidden again.
+    pub fn show_hex_dump(&mut self, shown: bool) {
+        if self.hex_dump.is_some() != shown {
+            self.wrapped = None;
+        }
+        self.hex_dump = match &self.value {
+            KeyValue::String(raw) if shown => Some(self.hex_dump.take().unwrap_or_else(|| hex_dump(raw))),
+            _ => None,
+        };
+    }
+
+    /// The lines a string is shown as, its hex dump or its text.
+    pub fn text_lines(&self) -> impl Iterator<Item = &str> {
+        let text = self.pretty.iter().filter(|_| self.hex_dump.is_none()).flat_map(|pretty| pretty.lines());
+        self.hex_dump.iter().flatten().map(String::as_str).chain(text)
+    }
+
+    /// The row each of the text lines starts at wrapped at `width` columns, followed by the rows
+    /// of the whole text. Kept until the width or the lines change.
+    pub fn wrapped_rows(&mut self, width: u16) -> &[usize] {
+        if self.wrapped.as_ref().is_none_or(|(wrapped_at, _)| *wrapped_at != width) {
+            let mut rows = vec![0];
+            for line in self.text_lines() {
+                rows.push(rows[rows.len() - 1] + wrap_offsets(line, width as usize).len());
+            }
+            self.wrapped = Some((width, rows));
+        }
+        self.wrapped.as_ref().map_or(&[], |(_, rows)| rows)
+    }
+
+    /// Adds the next page of members of this collection.
+    pub fn apply_members(&mut self, page: MemberPage) {
+        let Some(members) = self.members.as_mut() else { return };
+
+        members.cursor = page.cursor;
+        members.done = page.cursor == 0;
+        members.len = page.len;
+        self.value.extend(page.members);
+    }
+
+    pub fn age_as_human_delta(&self) -> String {
+        human_delta(self.fetched_at.elapsed().as_secs())
+    }
+}
+
+/// `raw` pretty-printed when it holds JSON.
+pub fn pretty_json(raw: &[u8]) -> Option<String> {
+    serde_json::from_slice::<serde_json::Value>(raw)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+}
+
+/// `bytes` as one line of text, with control characters and invalid UTF-8 escaped like `\x1b`.
+pub fn escape(bytes: &[u8]) -> String {
+    escape_bytes(bytes, false)
+}
+
+/// `bytes` as text like `escape`, keeping its line breaks and tabs.
+pub fn escape_lines(bytes: &[u8]) -> String {
+    escape_bytes(bytes, true)
+}
+
+fn escape_bytes(bytes: &[u8], multiline: bool) -> String {
+    let mut text = String::with_capacity(bytes.len());
+
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\n' | '\t' if multiline => text.push(c),
+                '\n' => text.push_str("\\n"),
+                '\r' => text.push_str("\\r"),
+                '\t' => text.push_str("\\t"),
+                c if c.is_ascii_control() => text.push_str(&format!("\\x{:02x}", c as u32)),
+                c if c.is_control() => text.extend(c.escape_unicode()),
+                c => text.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            text.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+
+    text
+}
+
+/// Whether `bytes` is not text: invalid UTF-8, or holding control characters other than
+/// whitespace.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')),
+        Err(_) => true,
+    }
+}
+
+/// `hexdump -C` style lines of `bytes`: the offset, 16 bytes in hex and the printable ones.
+pub fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes.chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex = chunk.iter()
+                .enumerate()
+                // A wider gap halfway through the line
+                .map(|(i, byte)| if i == 8 { format!(" {:02x}", byte) } else { format!("{:02x}", byte) })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii: String = chunk.iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect();
+
+            format!("{:08x}  {:<48}  |{}|", line * 16, hex, ascii)
+        })
+        .collect()
+}
+
+/// Byte offsets of the rows `line` takes wrapped at `width` columns. It is broken anywhere
+/// rather than between words, so the rows a value takes can be counted as it is shown.
+pub fn wrap_offsets(line: &str, width: usize) -> Vec<usize> {
+    let mut offsets = vec![0];
+    if width == 0 {
+        return offsets;
+    }
+
+    let mut row_width = 0;
+    for (i, c) in line.char_indices() {
+        let char_width = c.width().unwrap_or(0);
+        if row_width + char_width > width && row_width > 0 {
+            offsets.push(i);
+            row_width = 0;
+        }
+        row_width += char_width;
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_zset_ranges() {
+        assert_eq!(ZRange::parse_score(""), Some(ZRange::Rank));
+        assert_eq!(ZRange::parse_score("(1.5 +inf"), Some(ZRange::Score("(1.5".to_string(), "+inf".to_string())));
+        assert_eq!(ZRange::parse_score("-inf 10"), Some(ZRange::Score("-inf".to_string(), "10".to_string())));
+        assert_eq!(ZRange::parse_score("a 10"), None);
+        assert_eq!(ZRange::parse_score("10"), None);
+
+        assert_eq!(ZRange::parse_lex("[a (b"), Some(ZRange::Lex("[a".to_string(), "(b".to_string())));
+        assert_eq!(ZRange::parse_lex("- +"), Some(ZRange::Lex("-".to_string(), "+".to_string())));
+        assert_eq!(ZRange::parse_lex("a b"), None);
+    }
+
+    #[test]
+    fn finds_list_indexes_and_windows() {
+        let window = ListWindow { len: 10, start: 4, elements: vec!["e".to_string(); 3] };
+        assert_eq!(window.index("0"), Some(0));
+        assert_eq!(window.index(" 9 "), Some(9));
+        assert_eq!(window.index("10"), None);
+        assert_eq!(window.index("-1"), Some(9));
+        assert_eq!(window.index("-10"), Some(0));
+        assert_eq!(window.index("-11"), None);
+        assert_eq!(window.index("last"), None);
+
+        assert!(window.covers(4, 3));
+        assert!(window.covers(6, 1));
+        assert!(!window.covers(3, 1));
+        // Just past the window
+        assert!(!window.covers(7, 1));
+        assert!(!window.covers(5, 3));
+
+        let last = ListWindow { len: 10, start: 8, elements: vec!["e".to_string(); 2] };
+        assert!(last.covers(8, 5));
+        assert!(!last.covers(7, 5));
+
+        let empty = ListWindow { len: 0, start: 0, elements: Vec::new() };
+        assert_eq!(empty.index("0"), None);
+        assert_eq!(empty.index("-1"), None);
+        assert!(empty.covers(0, 20));
+    }
+
+    #[test]
+    fn escapes_control_and_invalid_bytes() {
+        assert_eq!(escape(b"user:1"), "user:1");
+        assert_eq!(escape("caf\u{e9}".as_bytes()), "caf\u{e9}");
+        assert_eq!(escape(b"bin:\xff\x00key\n"), "bin:\\xff\\x00key\\n");
+        assert_eq!(escape_lines(b"a\n\tb\x1b"), "a\n\tb\\x1b");
+
+        assert!(!is_binary(b"line\r\nline"));
+        assert!(is_binary(b"\x00\x01"));
+        assert!(is_binary(b"\xff"));
+    }
+
+    #[test]
+    fn dumps_bytes_as_hex() {
+        assert_eq!(hex_dump(b"Hello, world!\n\x00\xff\x10"), vec![
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 ff  |Hello, world!...|".to_string(),
+            "00000010  10                                                |.|".to_string(),
+        ]);
+        assert!(hex_dump(b"").is_empty());
+    }
+
+    #[test]
+    fn wraps_lines_by_display_width() {
+        assert_eq!(wrap_offsets("", 4), [0]);
+        assert_eq!(wrap_offsets("abcdefghij", 4), [0, 4, 8]);
+        assert_eq!(wrap_offsets("ab\u{4e16}\u{754c}cde", 4), [0, 5, 10]);
+    }
+}
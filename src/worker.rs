@@ -0,0 +1,309 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Sender},
+    },
+    thread,
+};
+use crossterm::event::KeyEvent;
+use redis::{self, RedisResult, Value};
+
+use crate::{
+    app::{parse_keyspace_info, KeyMetadata, KeyspaceInfo, RedisServer},
+    connection::{Node, SessionConnection},
+    database::RedisServerConf,
+    input::Event,
+    inspect::{self, KeyInfo},
+    stream::{self, EntryPage, GroupInfo, GroupPending},
+    value::{self, Collection, ListWindow, MemberPage, ValueQuery, ViewedValue},
+};
+
+static NEXT_WORKER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A reply from a worker thread, tagged with the worker and request it answers.
+pub struct WorkerEvent {
+    pub worker: u64,
+    pub request: u64,
+    pub reply: RedisReply,
+}
+
+pub enum RedisRequest {
+    Scan { node: Option<Node>, cursor: u64, pattern: String },
+    SelectDb(i64),
+    Keyspace,
+    /// The value of a key, read according to its type and the parts of it queried.
+    Fetch { key: Vec<u8>, query: ValueQuery },
+    /// The next page of members of a viewed collection.
+    Members { key: Vec<u8>, collection: Collection, cursor: u64, query: ValueQuery },
+    /// Other elements of a viewed list.
+    List { key: Vec<u8>, window: Range<usize> },
+    /// The next page of entries of a viewed stream, from the id `from`.
+    Entries { key: Vec<u8>, from: String, rev: bool },
+    /// Consumer groups of a stream.
+    Groups(Vec<u8>),
+    /// Consumers and pending entries of a stream's consumer group.
+    Pending { key: Vec<u8>, group: String },
+    /// Type, encoding, memory usage, expiry and access stats of a key.
+    Inspect(Vec<u8>),
+}
+
+pub enum RedisReply {
+    /// The connection is open. A single server has one `None` node, a cluster one per master.
+    Connected(RedisResult<Vec<Option<Node>>>),
+    /// Result of a connection test, the error already described for the user.
+    Tested(Result<(), String>),
+    Scanned(RedisResult<ScanPage>),
+    DbSelected(i64, RedisResult<()>),
+    Keyspace(RedisResult<Vec<KeyspaceInfo>>),
+    Value(Vec<u8>, RedisResult<Option<ViewedValue>>),
+    Members(Vec<u8>, RedisResult<MemberPage>),
+    List(Vec<u8>, RedisResult<ListWindow>),
+    Entries(Vec<u8>, RedisResult<EntryPage>),
+    Groups(Vec<u8>, RedisResult<Vec<GroupInfo>>),
+    Pending(Vec<u8>, RedisResult<GroupPending>),
+    Inspected(Vec<u8>, RedisResult<Option<KeyInfo>>),
+}
+
+pub struct ScanPage {
+    pub cursor: u64,
+    pub keys: Vec<(Vec<u8>, KeyMetadata)>,
+}
+
+/// Handle to the thread that owns a session's connection. Requests are run one at a time, in
+/// order. Dropping the handle stops the thread once its current request returns.
+pub struct Worker {
+    id: u64,
+    requests: Sender<(u64, RedisRequest)>,
+    next_request: u64,
+}
+
+impl Worker {
+    /// Opens a connection to `conf` on a new thread, answering with `RedisReply::Connected`.
+    pub fn connect(conf: RedisServerConf, events: Sender<Event<KeyEvent>>) -> Worker {
+        let id = NEXT_WORKER_ID.fetch_add(1, Ordering::Relaxed);
+        let (requests, rx) = mpsc::channel::<(u64, RedisRequest)>();
+
+        thread::spawn(move || {
+            let server = RedisServer::from(conf);
+            let connected = server.open_connection().and_then(|mut con| {
+                let nodes = match con.masters(server.seed_host())? {
+                    Some(masters) => masters.into_iter().map(Some).collect(),
+                    None => vec![None],
+                };
+                Ok((con, nodes))
+            });
+
+            let (mut con, nodes) = match connected {
+                Ok((con, nodes)) => {
+                    let reply = RedisReply::Connected(Ok(nodes.clone()));
+                    if events.send(Event::Redis(Box::new(WorkerEvent { worker: id, request: 0, reply }))).is_err() {
+                        return;
+                    }
+                    (con, nodes)
+                }
+                Err(e) => {
+                    let reply = RedisReply::Connected(Err(e));
+                    let _ = events.send(Event::Redis(Box::new(WorkerEvent { worker: id, request: 0, reply })));
+                    return;
+                }
+            };
+
+            for (request, job) in rx {
+                let reply = execute(&mut con, &nodes, job);
+                if events.send(Event::Redis(Box::new(WorkerEvent { worker: id, request, reply }))).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Worker { id, requests, next_request: 1 }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Queues `request`, returning the id its reply will carry.
+    pub fn send(&mut self, request: RedisRequest) -> u64 {
+        let id = self.next_request;
+        self.next_request += 1;
+        // A worker whose connection failed has stopped, its session is dropped on that reply
+        let _ = self.requests.send((id, request));
+        id
+    }
+}
+
+/// Opens and pings a connection to `conf` on a new thread, answering with `RedisReply::Tested`
+/// tagged with the returned id.
+pub fn test_connection(conf: RedisServerConf, events: Sender<Event<KeyEvent>>) -> u64 {
+    let id = NEXT_WORKER_ID.fetch_add(1, Ordering::Relaxed);
+
+    thread::spawn(move || {
+        let server = RedisServer::from(conf);
+        let result = server.open_connection()
+            .and_then(|mut con| redis::cmd("PING").query::<String>(&mut con))
+            .map(|_| ())
+            .map_err(|e| server.describe_connect_error(&e));
+        let _ = events.send(Event::Redis(Box::new(WorkerEvent { worker: id, request: 0, reply: RedisReply::Tested(result) })));
+    });
+
+    id
+}
+
+fn execute(con: &mut SessionConnection, nodes: &[Option<Node>], request: RedisRequest) -> RedisReply {
+    match request {
+        RedisRequest::Scan { node, cursor, pattern } => RedisReply::Scanned(scan(con, node.as_ref(), cursor, &pattern)),
+        RedisRequest::SelectDb(db) => {
+            let result = redis::cmd("SELECT").arg(db).query::<()>(con);
+            if result.is_ok() {
+                con.set_db(db);
+            }
+            RedisReply::DbSelected(db, result)
+        }
+        RedisRequest::Keyspace => RedisReply::Keyspace(keyspace(con, nodes)),
+        RedisRequest::Fetch { key, query } => {
+            let result = value::fetch(con, &key, &query);
+            RedisReply::Value(key, result)
+        }
+        RedisRequest::Members { key, collection, cursor, query } => {
+            let result = value::read_members(con, &key, collection, cursor, &query);
+            RedisReply::Members(key, result)
+        }
+        RedisRequest::List { key, window } => {
+            let result = value::read_list(con, &key, window);
+            RedisReply::List(key, result)
+        }
+        RedisRequest::Entries { key, from, rev } => {
+            let result = stream::read_entries(con, &key, &from, rev);
+            RedisReply::Entries(key, result)
+        }
+        RedisRequest::Groups(key) => {
+            let result = stream::read_groups(con, &key);
+            RedisReply::Groups(key, result)
+        }
+        RedisRequest::Pending { key, group } => {
+            let result = stream::read_pending(con, &key, &group);
+            RedisReply::Pending(key, result)
+        }
+        RedisRequest::Inspect(key) => {
+            let result = inspect::read_key_info(con, &key);
+            RedisReply::Inspected(key, result)
+        }
+    }
+}
+
+fn scan(con: &mut SessionConnection, node: Option<&Node>, cursor: u64, pattern: &str) -> RedisResult<ScanPage> {
+    let mut cmd = redis::cmd("SCAN");
+    cmd.cursor_arg(cursor)
+        .arg("MATCH")
+        .arg(pattern);
+    let (cursor, keys): (u64, Vec<Vec<u8>>) = con.query_node(&cmd, node)?;
+
+    let metadata = read_metadata(con, &keys)?;
+    let keys = keys.into_iter().zip(metadata).collect();
+
+    Ok(ScanPage { cursor, keys })
+}
+
+/// TYPE, TTL and MEMORY USAGE of a batch of scanned keys, in two round trips. MEMORY USAGE may
+/// be denied by an ACL, renamed away, or missing before Redis 4, so the keys only lack a size then.
+fn read_metadata(con: &mut SessionConnection, keys: &[Vec<u8>]) -> RedisResult<Vec<KeyMetadata>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (replies, sizes): (Vec<Value>, RedisResult<Vec<Option<u64>>>) = match con {
+        // Routed by key, the batch all goes to the node it was scanned on
+        SessionConnection::Cluster(cluster) => {
+            let mut pipe = redis::cluster::cluster_pipe();
+            for key in keys {
+                pipe.cmd("TYPE").arg(key).cmd("TTL").arg(key);
+            }
+            let replies = pipe.query(cluster)?;
+
+            let mut pipe = redis::cluster::cluster_pipe();
+            for key in keys {
+                pipe.cmd("MEMORY").arg("USAGE").arg(key);
+            }
+            (replies, pipe.query(cluster))
+        }
+        con => {
+            let mut pipe = redis::pipe();
+            for key in keys {
+                pipe.cmd("TYPE").arg(key).cmd("TTL").arg(key);
+            }
+            let replies = pipe.query(con)?;
+
+            let mut pipe = redis::pipe();
+            for key in keys {
+                pipe.cmd("MEMORY").arg("USAGE").arg(key);
+            }
+            (replies, pipe.query(con))
+        }
+    };
+
+    let type_and_ttl: Vec<(String, i64)> = replies.chunks(2)
+        .map(|reply| Ok((redis::from_redis_value(&reply[0])?, redis::from_redis_value(&reply[1])?)))
+        .collect::<RedisResult<_>>()?;
+    let sizes = sizes.unwrap_or_else(|_| vec![None; keys.len()]);
+
+    Ok(type_and_ttl.into_iter()
+        .zip(sizes)
+        .map(|((value_type, ttl), size)| KeyMetadata {
+            // Keys deleted since the scan are `none`, those expired since -2, persistent ones -1
+            _type: Some(value_type).filter(|value_type| value_type != "none"),
+            ttl: u64::try_from(ttl).ok(),
+            size,
+        })
+        .collect())
+}
+
+fn keyspace(con: &mut SessionConnection, nodes: &[Option<Node>]) -> RedisResult<Vec<KeyspaceInfo>> {
+    if matches!(con, SessionConnection::Cluster(_)) {
+        return cluster_keyspace(con, nodes);
+    }
+
+    let info: String = redis::cmd("INFO").arg("keyspace").query(con)?;
+    let mut databases = parse_keyspace_info(&info);
+
+    // CONFIG is often disabled on managed services, so fall back to the default of 16
+    let count = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("databases")
+        .query::<(String, i64)>(con)
+        .map(|(_, count)| count)
+        .unwrap_or(16)
+        .max(databases.iter().map(|info| info.db + 1).max().unwrap_or(0));
+
+    for db in 0..count {
+        if !databases.iter().any(|info| info.db == db) {
+            databases.push(KeyspaceInfo { db, keys: 0, expires: 0, avg_ttl: 0 });
+        }
+    }
+    databases.sort_by_key(|info| info.db);
+
+    Ok(databases)
+}
+
+/// A cluster only has db 0, whose stats are summed over all masters.
+fn cluster_keyspace(con: &mut SessionConnection, nodes: &[Option<Node>]) -> RedisResult<Vec<KeyspaceInfo>> {
+    let mut total = KeyspaceInfo { db: 0, keys: 0, expires: 0, avg_ttl: 0 };
+    let mut ttl_nodes = 0;
+
+    let cmd = redis::cmd("INFO").arg("keyspace").clone();
+    for node in nodes {
+        let info: String = con.query_node(&cmd, node.as_ref())?;
+        for info in parse_keyspace_info(&info).into_iter().filter(|info| info.db == 0) {
+            total.keys += info.keys;
+            total.expires += info.expires;
+            if info.avg_ttl > 0 {
+                total.avg_ttl += info.avg_ttl;
+                ttl_nodes += 1;
+            }
+        }
+    }
+    total.avg_ttl = total.avg_ttl.checked_div(ttl_nodes).unwrap_or(0);
+
+    Ok(vec![total])
+}